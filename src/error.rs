@@ -1,9 +1,31 @@
 //! Implements the type `Error`.
 
+/// Enumerates the errors of this crate. Variants may be added, e.g. by
+/// enabling features, hence matches must include a wildcard arm.
 #[derive(thiserror::Error, Debug)]
+#[non_exhaustive]
 pub enum Error {
     #[error(transparent)]
     Io(#[from] std::io::Error),
     #[error(transparent)]
     Json(#[from] serde_json::Error),
+    #[error(transparent)]
+    Fmt(#[from] std::fmt::Error),
+    #[error("the manifest pointer '{0}' does not point to a value")]
+    ManifestPointer(String),
+    #[error("the manifest is not JSON, it starts with '{0}'")]
+    ManifestNotJson(String),
+    #[error("the development server at '{0}' did not respond in time")]
+    DevServerUnavailable(String),
+    #[error("the mode '{0}' is neither 'development' nor 'production'")]
+    InvalidMode(String),
+    #[cfg(feature = "yaml")]
+    #[error(transparent)]
+    Yaml(#[from] serde_yaml::Error),
+    #[cfg(feature = "toml")]
+    #[error(transparent)]
+    Toml(#[from] toml::de::Error),
+    #[cfg(feature = "glob")]
+    #[error(transparent)]
+    Pattern(#[from] glob::PatternError),
 }
@@ -0,0 +1,153 @@
+//! This module implements the integration into dioxus, i.e. the method
+//! `Vite::elements`, which renders the tags required to include Vite's
+//! resources as elements of an `rsx!` tree.
+
+use crate::resource::{preload_destination, AssetKind, ResolvedAsset};
+use crate::vite::Vite;
+
+use dioxus::prelude::*;
+
+impl Vite {
+    /// Returns the elements required to include the given entrypoints, e.g.
+    /// within the `head` of a document during server-side rendering.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use in_vite::Vite;
+    /// use dioxus::prelude::*;
+    ///
+    /// let vite = Vite::default();
+    /// let document = rsx! {
+    ///     head {
+    ///         {vite.elements(&["app.js"])}
+    ///     }
+    /// };
+    ///
+    /// let html = dioxus_ssr::render_element(document);
+    /// ```
+    ///
+    pub fn elements(&self, entrypoints: &[&str]) -> Element {
+        let assets = self.resolve(entrypoints.to_vec())?;
+
+        rsx! {
+            for asset in assets {
+                {asset_element(asset, self.defer_classic())}
+            }
+        }
+    }
+}
+
+/// Converts a resolved asset into the appropriate element, deferring classic
+/// scripts if `defer`.
+fn asset_element(asset: ResolvedAsset, defer: bool) -> Element {
+    let url = asset.url;
+
+    match asset.kind {
+        AssetKind::Stylesheet => rsx! { link { rel: "stylesheet", href: url } },
+        AssetKind::Module => rsx! { script { r#type: "module", src: url } },
+        AssetKind::Script => rsx! { script { defer: if defer { true }, src: url } },
+        AssetKind::PreloadModule => rsx! { link { rel: "modulepreload", href: url } },
+        AssetKind::PreloadStylesheet => rsx! { link { rel: "preload", r#as: "style", href: url } },
+        AssetKind::PreloadAsset => {
+            let (destination, crossorigin) = preload_destination(&url).unwrap_or(("fetch", true));
+            rsx! {
+                link {
+                    rel: "preload",
+                    r#as: destination,
+                    crossorigin: if crossorigin { "" },
+                    href: url,
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::vite::{Vite, ViteMode, ViteOptions};
+
+    const SAMPLE_MANIFEST: &str = include_str!("../../test/sample_manifest.json");
+    const ASSETS_MANIFEST: &str = include_str!("../../test/assets_manifest.json");
+
+    #[test]
+    fn can_dioxus_render_production() {
+        let opts = ViteOptions::default()
+            .mode(ViteMode::Production)
+            .source(Some(SAMPLE_MANIFEST.to_string()));
+
+        let vite = Vite::with_options(opts);
+        let html = dioxus_ssr::render_element(vite.elements(&["views/foo.js"]));
+
+        let expected = concat!(
+            r#"<link rel="stylesheet" href="/assets/shared-ChJ_j-JJ.css"/>"#,
+            r#"<link rel="stylesheet" href="/assets/foo-5UjPuW-k.css"/>"#,
+            r#"<script type="module" src="/assets/foo-BRBmoGS9.js"></script>"#,
+            r#"<link rel="modulepreload" href="/assets/shared-B7PI925R.js"/>"#,
+        );
+
+        assert_eq!(html, expected);
+    }
+
+    #[test]
+    fn can_dioxus_render_development() {
+        let opts = ViteOptions::default().mode(ViteMode::Development);
+
+        let vite = Vite::with_options(opts);
+        let html = dioxus_ssr::render_element(vite.elements(&["app.js"]));
+
+        let expected = concat!(
+            r#"<script type="module" src="http://localhost:5173/@vite/client"></script>"#,
+            r#"<script type="module" src="http://localhost:5173/app.js"></script>"#,
+        );
+
+        assert_eq!(html, expected);
+    }
+
+    #[test]
+    fn can_dioxus_render_asset_preloads() {
+        let opts = ViteOptions::default()
+            .mode(ViteMode::Production)
+            .preload_assets(true)
+            .source(Some(ASSETS_MANIFEST.to_string()));
+
+        let vite = Vite::with_options(opts);
+        let html = dioxus_ssr::render_element(vite.elements(&["views/app.js"]));
+
+        let expected = concat!(
+            r#"<script type="module" src="/assets/app-C4xq9LmZ.js"></script>"#,
+            r#"<link rel="preload" as="fetch" crossorigin="" href="/assets/data-Bz8kT2Qa.json"/>"#,
+            r#"<link rel="preload" as="fetch" crossorigin="" href="/assets/engine-D1rPq7Xw.wasm"/>"#,
+        );
+
+        assert_eq!(html, expected);
+    }
+
+    #[test]
+    fn dioxus_defers_classic_scripts() {
+        let opts = ViteOptions::default()
+            .mode(ViteMode::Production)
+            .source(Some(include_str!("../../test/umd_manifest.json")));
+
+        let vite = Vite::with_options(opts);
+        let html = dioxus_ssr::render_element(vite.elements(&["lib/widget.js"]));
+
+        assert_eq!(
+            html,
+            r#"<script defer=true src="/assets/widget-Dk3m8PqA.umd.js"></script>"#
+        );
+
+        let opts = ViteOptions::default()
+            .mode(ViteMode::Production)
+            .defer_classic(false)
+            .source(Some(include_str!("../../test/umd_manifest.json")));
+
+        let vite = Vite::with_options(opts);
+        let html = dioxus_ssr::render_element(vite.elements(&["lib/widget.js"]));
+
+        assert_eq!(
+            html,
+            r#"<script src="/assets/widget-Dk3m8PqA.umd.js"></script>"#
+        );
+    }
+}
@@ -0,0 +1,183 @@
+//! This module implements the component `ViteAssets`, which renders the tags
+//! required to include Vite's resources into a leptos view.
+
+use crate::resource::{preload_destination, AssetKind, ResolvedAsset};
+use crate::vite::Vite;
+
+use std::sync::Arc;
+
+use leptos::prelude::*;
+
+/// Renders the tags required to include the given entrypoints, e.g. within
+/// the `<head>` of the application's shell during server-side rendering.
+///
+/// Should the entrypoints fail to resolve, e.g. due to a missing manifest,
+/// nothing is rendered and the error is logged instead.
+///
+/// # Examples
+///
+/// ```
+/// use std::sync::Arc;
+///
+/// use in_vite::{Vite, ViteAssets};
+/// use leptos::prelude::*;
+///
+/// #[component]
+/// fn Shell(vite: Arc<Vite>) -> impl IntoView {
+///     view! {
+///         <head>
+///             <ViteAssets vite=vite entrypoints=vec!["app.js".to_string()] />
+///         </head>
+///     }
+/// }
+///
+/// ```
+///
+#[component]
+pub fn ViteAssets(vite: Arc<Vite>, #[prop(into)] entrypoints: Vec<String>) -> impl IntoView {
+    let entrypoints = entrypoints.iter().map(|e| e.as_str()).collect();
+    let assets = vite.resolve(entrypoints).unwrap_or_else(|err| {
+        tracing::error!("failed to resolve the entrypoints: {err}");
+        vec![]
+    });
+
+    let defer = vite.defer_classic();
+    assets
+        .into_iter()
+        .map(|asset| asset_view(asset, defer))
+        .collect_view()
+}
+
+/// Converts a resolved asset into the appropriate tag, deferring classic
+/// scripts if `defer`.
+fn asset_view(asset: ResolvedAsset, defer: bool) -> AnyView {
+    let url = asset.url;
+
+    match asset.kind {
+        AssetKind::Stylesheet => view! { <link rel="stylesheet" href=url /> }.into_any(),
+        AssetKind::Module => view! { <script type="module" src=url></script> }.into_any(),
+        AssetKind::Script => view! { <script defer=defer src=url></script> }.into_any(),
+        AssetKind::PreloadModule => view! { <link rel="modulepreload" href=url /> }.into_any(),
+        AssetKind::PreloadStylesheet => leptos::html::link()
+            .rel("preload")
+            .r#as("style")
+            .href(url)
+            .into_any(),
+        AssetKind::PreloadAsset => {
+            let (destination, crossorigin) = preload_destination(&url).unwrap_or(("fetch", true));
+            let crossorigin = crossorigin.then_some("");
+            // `as` is a keyword, hence the builder is used instead of `view!`.
+            leptos::html::link()
+                .rel("preload")
+                .r#as(destination)
+                .crossorigin(crossorigin)
+                .href(url)
+                .into_any()
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::ViteAssets;
+    use crate::vite::{Vite, ViteMode, ViteOptions};
+
+    use std::sync::Arc;
+
+    use leptos::prelude::*;
+    use tracing_test::traced_test;
+
+    const SAMPLE_MANIFEST: &str = include_str!("../../test/sample_manifest.json");
+    const ASSETS_MANIFEST: &str = include_str!("../../test/assets_manifest.json");
+
+    #[test]
+    fn can_leptos_render_production() {
+        let opts = ViteOptions::default()
+            .mode(ViteMode::Production)
+            .source(Some(SAMPLE_MANIFEST.to_string()));
+
+        let vite = Arc::new(Vite::with_options(opts));
+        let html = view! { <ViteAssets vite=vite entrypoints=vec!["views/foo.js".to_string()] /> }
+            .to_html();
+
+        let expected = concat!(
+            r#"<link rel="stylesheet" href="/assets/shared-ChJ_j-JJ.css">"#,
+            r#"<link rel="stylesheet" href="/assets/foo-5UjPuW-k.css">"#,
+            r#"<script type="module" src="/assets/foo-BRBmoGS9.js"></script>"#,
+            r#"<link rel="modulepreload" href="/assets/shared-B7PI925R.js">"#,
+            // Marker inserted by leptos to hydrate the list of tags.
+            "<!>",
+        );
+
+        assert_eq!(html, expected);
+    }
+
+    #[test]
+    fn can_leptos_render_development() {
+        let opts = ViteOptions::default().mode(ViteMode::Development);
+
+        let vite = Arc::new(Vite::with_options(opts));
+        let html =
+            view! { <ViteAssets vite=vite entrypoints=vec!["app.js".to_string()] /> }.to_html();
+
+        let expected = concat!(
+            r#"<script type="module" src="http://localhost:5173/@vite/client"></script>"#,
+            r#"<script type="module" src="http://localhost:5173/app.js"></script>"#,
+            "<!>",
+        );
+
+        assert_eq!(html, expected);
+    }
+
+    #[test]
+    fn can_leptos_render_asset_preloads() {
+        let opts = ViteOptions::default()
+            .mode(ViteMode::Production)
+            .preload_assets(true)
+            .source(Some(ASSETS_MANIFEST.to_string()));
+
+        let vite = Arc::new(Vite::with_options(opts));
+        let html = view! { <ViteAssets vite=vite entrypoints=vec!["views/app.js".to_string()] /> }
+            .to_html();
+
+        let expected = concat!(
+            r#"<script type="module" src="/assets/app-C4xq9LmZ.js"></script>"#,
+            r#"<link rel="preload" as="fetch" crossorigin="" href="/assets/data-Bz8kT2Qa.json">"#,
+            r#"<link rel="preload" as="fetch" crossorigin="" href="/assets/engine-D1rPq7Xw.wasm">"#,
+            "<!>",
+        );
+
+        assert_eq!(html, expected);
+    }
+
+    #[test]
+    #[traced_test]
+    fn leptos_renders_nothing_without_manifest() {
+        let opts = ViteOptions::default()
+            .mode(ViteMode::Production)
+            .manifest_path("test/missing_manifest.json");
+
+        let vite = Arc::new(Vite::with_options(opts));
+        let html =
+            view! { <ViteAssets vite=vite entrypoints=vec!["app.js".to_string()] /> }.to_html();
+
+        assert_eq!(html, "<!>");
+        assert!(logs_contain("failed to resolve the entrypoints"));
+    }
+
+    #[test]
+    fn leptos_defers_classic_scripts() {
+        let opts = ViteOptions::default()
+            .mode(ViteMode::Production)
+            .source(Some(include_str!("../../test/umd_manifest.json")));
+
+        let vite = Arc::new(Vite::with_options(opts));
+        let html = view! { <ViteAssets vite=vite entrypoints=vec!["lib/widget.js".to_string()] /> }
+            .to_html();
+
+        assert_eq!(
+            html,
+            r#"<script defer src="/assets/widget-Dk3m8PqA.umd.js"></script><!>"#
+        );
+    }
+}
@@ -1,16 +1,64 @@
 //! This module implements the necessary traits required to make `crate::Vite`
 //! callable in minijinja templates.
 
-use crate::vite::{Vite, ViteReactRefresh};
+use crate::integrations::{minijinja_entrypoints, split_entrypoints};
+use crate::vite::{Vite, ViteBaseTag, ViteDevBanner, ViteHmrUrl, VitePrefetch, ViteReactRefresh};
 
 use std::sync::Arc;
 
 use minijinja::value::{from_args, Kwargs, Object, ObjectRepr};
-use minijinja::{Error, Value};
+use minijinja::{Environment, Error, ErrorKind, Value};
+
+impl Vite {
+    /// Registers the instance as filter `name`, rendering the HTML required to
+    /// include the piped entrypoints, e.g. `{{ "app.js" | vite }}`. Both a
+    /// comma-separated string and a list of entrypoints may be piped.
+    ///
+    /// The filter complements binding the instance as global; the filter holds
+    /// a clone sharing the cached manifest.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use in_vite::Vite;
+    /// use minijinja::{Environment, Value, Error};
+    ///
+    /// fn main() -> Result<(), Error> {
+    ///     let vite = Vite::default();
+    ///     let mut env = Environment::new();
+    ///     vite.add_minijinja_filter(&mut env, "vite");
+    ///     env.add_global("vite", Value::from_object(vite));
+    ///
+    ///     let template = env.render_str(r#"{{ "" | vite }}"#, Value::UNDEFINED)?;
+    ///     Ok(())
+    /// }
+    /// ```
+    pub fn add_minijinja_filter(&self, env: &mut Environment<'_>, name: &'static str) {
+        let vite = self.clone();
+        env.add_filter(name, move |resources: Value| -> Result<Value, Error> {
+            let entrypoints: Vec<String> = match resources.as_str() {
+                Some(resources) => split_entrypoints(resources),
+                None => resources.try_iter()?.map(|e| e.to_string()).collect(),
+            };
+            let entrypoints = entrypoints.iter().map(|e| e.as_str()).collect();
+
+            let code = vite.to_html(entrypoints).map_err(|err| {
+                Error::new(ErrorKind::InvalidOperation, "failed to render entrypoints")
+                    .with_source(err)
+            })?;
+
+            Ok(html_value(&vite, code))
+        });
+    }
+}
 
 /// Allows for instances fof Vite to be bound as values and added to the
 /// minijinja environment.
 ///
+/// Besides being callable, the value provides the method `has_css`, which
+/// checks whether an entrypoint includes any stylesheet, e.g.
+/// `{% if vite.has_css("app.js") %}`.
+///
 /// # Examples
 ///
 /// ```
@@ -38,14 +86,48 @@ impl Object for Vite {
         _state: &minijinja::State<'_, '_>,
         args: &[minijinja::Value],
     ) -> Result<Value, Error> {
-        let (_, kwargs) = from_args::<(&[Value], Kwargs)>(&args)?;
+        let (_, kwargs) = from_args::<(&[Value], Kwargs)>(args)?;
 
         // The resources passed here are treated as entrypoint for vite.
-        let entrypoints: Vec<String> = kwargs.get("resources")?;
+        let entrypoints = minijinja_entrypoints(&kwargs)?;
         let entrypoints = entrypoints.iter().map(|e| e.as_str()).collect();
 
-        let code = self.to_html(entrypoints).unwrap();
-        Ok(Value::from_safe_string(code))
+        // The mode may be overridden per call, e.g. to force development tags.
+        let mode: Option<&str> = kwargs.get("mode")?;
+        let code = match mode {
+            Some(mode) => {
+                let mode = mode.parse().map_err(|err| {
+                    Error::new(ErrorKind::InvalidOperation, "invalid mode").with_source(err)
+                })?;
+                self.to_html_with_mode(entrypoints, mode)
+            }
+            None => self.to_html(entrypoints),
+        }
+        .map_err(|err| {
+            Error::new(ErrorKind::InvalidOperation, "failed to render entrypoints").with_source(err)
+        })?;
+
+        Ok(html_value(self, code))
+    }
+
+    fn call_method(
+        self: &Arc<Self>,
+        _state: &minijinja::State<'_, '_>,
+        method: &str,
+        args: &[minijinja::Value],
+    ) -> Result<Value, Error> {
+        match method {
+            "has_css" => {
+                let (entry,) = from_args::<(&str,)>(args)?;
+                let has_css = self.entry_has_css(entry).map_err(|err| {
+                    Error::new(ErrorKind::InvalidOperation, "failed to resolve entrypoint")
+                        .with_source(err)
+                })?;
+
+                Ok(Value::from(has_css))
+            }
+            _ => Err(Error::from(ErrorKind::UnknownMethod)),
+        }
     }
 
     fn is_true(self: &Arc<Self>) -> bool {
@@ -90,10 +172,179 @@ impl Object for ViteReactRefresh {
     }
 }
 
+/// Allows for instances of ViteDevBanner to be bound as values and added to the
+/// minijinja environment.
+///
+/// # Examples
+///
+/// ```
+/// use in_vite::{Vite, ViteDevBanner};
+/// use minijinja::{Environment, Value, Error};
+///
+/// fn main() -> Result<(), Error> {
+///     let vite = Vite::default();
+///     let vite_dev_banner = ViteDevBanner::new(vite.host(), vite.mode());
+///     let mut env = Environment::new();
+///     env.add_global("vite_dev_banner", Value::from_object(vite_dev_banner));
+///
+///     let template = env.render_str(r#"{{ vite_dev_banner() }}"#, Value::UNDEFINED)?;
+///     Ok(())
+/// }
+///
+/// ```
+///
+impl Object for ViteDevBanner {
+    fn repr(self: &Arc<Self>) -> ObjectRepr {
+        ObjectRepr::Plain
+    }
+
+    fn call(
+        self: &Arc<Self>,
+        _state: &minijinja::State<'_, '_>,
+        _args: &[minijinja::Value],
+    ) -> Result<Value, Error> {
+        let code = self.dev_banner_html();
+
+        Ok(Value::from_safe_string(code))
+    }
+}
+
+/// Allows for instances of ViteHmrUrl to be bound as values and added to the
+/// minijinja environment.
+///
+/// # Examples
+///
+/// ```
+/// use in_vite::{Vite, ViteHmrUrl};
+/// use minijinja::{Environment, Value, Error};
+///
+/// fn main() -> Result<(), Error> {
+///     let vite = Vite::default();
+///     let vite_hmr_url = ViteHmrUrl::new(vite.host(), vite.mode());
+///     let mut env = Environment::new();
+///     env.add_global("vite_hmr_url", Value::from_object(vite_hmr_url));
+///
+///     let template = env.render_str(r#"{{ vite_hmr_url() }}"#, Value::UNDEFINED)?;
+///     Ok(())
+/// }
+///
+/// ```
+///
+impl Object for ViteHmrUrl {
+    fn repr(self: &Arc<Self>) -> ObjectRepr {
+        ObjectRepr::Plain
+    }
+
+    fn call(
+        self: &Arc<Self>,
+        _state: &minijinja::State<'_, '_>,
+        _args: &[minijinja::Value],
+    ) -> Result<Value, Error> {
+        let url = self.hmr_url();
+
+        Ok(Value::from_safe_string(url))
+    }
+}
+
+/// Allows for instances of ViteBaseTag to be bound as values and added to the
+/// minijinja environment.
+///
+/// # Examples
+///
+/// ```
+/// use in_vite::{Vite, ViteBaseTag};
+/// use minijinja::{Environment, Value, Error};
+///
+/// fn main() -> Result<(), Error> {
+///     let vite = Vite::default();
+///     let vite_base_tag = ViteBaseTag::new(vite.base());
+///     let mut env = Environment::new();
+///     env.add_global("vite_base_tag", Value::from_object(vite_base_tag));
+///
+///     let template = env.render_str(r#"{{ vite_base_tag() }}"#, Value::UNDEFINED)?;
+///     Ok(())
+/// }
+///
+/// ```
+///
+impl Object for ViteBaseTag {
+    fn repr(self: &Arc<Self>) -> ObjectRepr {
+        ObjectRepr::Plain
+    }
+
+    fn call(
+        self: &Arc<Self>,
+        _state: &minijinja::State<'_, '_>,
+        _args: &[minijinja::Value],
+    ) -> Result<Value, Error> {
+        let tag = self.base_tag();
+
+        Ok(Value::from_safe_string(tag))
+    }
+}
+
+/// Allows for instances of VitePrefetch to be bound as values and added to the
+/// minijinja environment.
+///
+/// # Examples
+///
+/// ```
+/// use in_vite::{Vite, VitePrefetch};
+/// use minijinja::{Environment, Value, Error};
+///
+/// fn main() -> Result<(), Error> {
+///     let vite = Vite::default();
+///     let vite_prefetch = VitePrefetch::new(&vite);
+///     let mut env = Environment::new();
+///     env.add_global("vite_prefetch", Value::from_object(vite_prefetch));
+///
+///     let template = env.render_str(r#"{{ vite_prefetch(resources="") }}"#, Value::UNDEFINED)?;
+///     Ok(())
+/// }
+///
+/// ```
+///
+impl Object for VitePrefetch {
+    fn repr(self: &Arc<Self>) -> ObjectRepr {
+        ObjectRepr::Plain
+    }
+
+    fn call(
+        self: &Arc<Self>,
+        _state: &minijinja::State<'_, '_>,
+        args: &[minijinja::Value],
+    ) -> Result<Value, Error> {
+        let (_, kwargs) = from_args::<(&[Value], Kwargs)>(args)?;
+
+        let entrypoints = minijinja_entrypoints(&kwargs)?;
+        let entrypoints = entrypoints.iter().map(|e| e.as_str()).collect();
+
+        let code = self.prefetch(entrypoints).map_err(|err| {
+            Error::new(
+                ErrorKind::InvalidOperation,
+                "failed to prefetch entrypoints",
+            )
+            .with_source(err)
+        })?;
+
+        Ok(Value::from_safe_string(code))
+    }
+}
+
+/// Wraps the HTML rendered by `vite` into a value, which is marked safe unless
+/// configured otherwise.
+fn html_value(vite: &Vite, code: String) -> Value {
+    if vite.mark_safe() {
+        Value::from_safe_string(code)
+    } else {
+        Value::from(code)
+    }
+}
+
 #[cfg(test)]
 mod test {
 
-    use super::{Vite, ViteReactRefresh};
+    use super::{Vite, ViteBaseTag, ViteDevBanner, ViteHmrUrl, VitePrefetch, ViteReactRefresh};
     use crate::vite::{ViteMode, ViteOptions};
     use minijinja::Environment;
     use minijinja::Value;
@@ -138,14 +389,56 @@ mod test {
             )
             .expect("Should work.");
 
-        let expected = r#"<link rel="stylesheet" href="assets/foo-5UjPuW-k.css" />
-<link rel="stylesheet" href="assets/shared-ChJ_j-JJ.css" />
-<script type="module" src="assets/foo-BRBmoGS9.js"></script>
-<link rel="modulepreload" href="assets/shared-B7PI925R.js" />"#;
+        let expected = r#"<link rel="stylesheet" href="/assets/shared-ChJ_j-JJ.css" />
+<link rel="stylesheet" href="/assets/foo-5UjPuW-k.css" />
+<script type="module" src="/assets/foo-BRBmoGS9.js"></script>
+<link rel="modulepreload" href="/assets/shared-B7PI925R.js" />"#;
 
         assert_eq!(result, expected);
     }
 
+    #[test]
+    fn can_minijinja_inject_comma_separated_resources() {
+        let opts = ViteOptions::default()
+            .mode(ViteMode::Development)
+            .source(Some(SAMPLE_MANIFEST.to_string()));
+
+        let vite = Vite::with_options(opts);
+        let mut env = Environment::new();
+        env.add_global("vite", Value::from_object(vite));
+        let result = env
+            .render_str(
+                r#"{{ vite(resources="app.js, admin.js") }}"#,
+                Value::UNDEFINED,
+            )
+            .expect("Should work.");
+
+        let expected = r#"<script type="module" src="http://localhost:5173/@vite/client"></script>
+<script type="module" src="http://localhost:5173/app.js"></script>
+<script type="module" src="http://localhost:5173/admin.js"></script>"#;
+
+        assert_eq!(result, expected);
+    }
+
+    #[test]
+    fn can_minijinja_check_entry_for_css() {
+        let opts = ViteOptions::default()
+            .mode(ViteMode::Production)
+            .source(Some(SAMPLE_MANIFEST.to_string()));
+
+        let vite = Vite::with_options(opts);
+        let mut env = Environment::new();
+        env.add_global("vite", Value::from_object(vite));
+        let result = env
+            .render_str(
+                r#"{% if vite.has_css("views/foo.js") %}yes{% endif %}{% if vite.has_css("baz.js") %}yes{% endif %}"#,
+                Value::UNDEFINED,
+            )
+            .expect("Should work.");
+
+        assert_eq!(result, "yes");
+    }
+
     #[test]
     fn can_minijinja_inject_react_refresh_development() {
         let opts = ViteOptions::default()
@@ -189,4 +482,194 @@ window.__vite_plugin_react_preamble_installed__ = true
 
         assert_eq!(result, expected);
     }
+
+    #[test]
+    fn can_minijinja_inject_dev_banner_development() {
+        let opts = ViteOptions::default().mode(ViteMode::Development);
+
+        let vite = Vite::with_options(opts);
+        let vite_dev_banner = ViteDevBanner::new(vite.host(), vite.mode());
+        let mut env = Environment::new();
+        env.add_global("vite_dev_banner", Value::from_object(vite_dev_banner));
+        let result = env
+            .render_str(r#"{{ vite_dev_banner() }}"#, Value::UNDEFINED)
+            .expect("Should work.");
+
+        assert!(result.contains(r#"<div id="in-vite-dev-banner" hidden"#));
+        assert!(result.contains(r#"import("http://localhost:5173/@vite/client")"#));
+    }
+
+    #[test]
+    fn minijinja_injects_nothing_dev_banner_production() {
+        let opts = ViteOptions::default().mode(ViteMode::Production);
+
+        let vite = Vite::with_options(opts);
+        let vite_dev_banner = ViteDevBanner::new(vite.host(), vite.mode());
+        let mut env = Environment::new();
+        env.add_global("vite_dev_banner", Value::from_object(vite_dev_banner));
+        let result = env
+            .render_str(r#"{{ vite_dev_banner() }}"#, Value::UNDEFINED)
+            .expect("Should work.");
+
+        assert_eq!(result, "");
+    }
+
+    #[test]
+    fn minijinja_treats_missing_resources_as_empty() {
+        let opts = ViteOptions::default()
+            .mode(ViteMode::Production)
+            .source(Some(SAMPLE_MANIFEST.to_string()));
+
+        let vite = Vite::with_options(opts);
+        let mut env = Environment::new();
+        env.add_global("vite", Value::from_object(vite));
+        let result = env
+            .render_str(r#"{{ vite() }}"#, Value::UNDEFINED)
+            .expect("Should work.");
+
+        assert_eq!(result, "");
+    }
+
+    #[test]
+    fn can_minijinja_inject_hmr_url_development() {
+        let opts = ViteOptions::default().mode(ViteMode::Development);
+
+        let vite = Vite::with_options(opts);
+        let vite_hmr_url = ViteHmrUrl::new(vite.host(), vite.mode());
+        let mut env = Environment::new();
+        env.add_global("vite_hmr_url", Value::from_object(vite_hmr_url));
+        let result = env
+            .render_str(r#"{{ vite_hmr_url() }}"#, Value::UNDEFINED)
+            .expect("Should work.");
+
+        assert_eq!(result, "ws://localhost:5173/");
+    }
+
+    #[test]
+    fn minijinja_escapes_unless_marked_safe() {
+        let opts = ViteOptions::default()
+            .mode(ViteMode::Production)
+            .mark_safe(false)
+            .source(Some(SAMPLE_MANIFEST.to_string()));
+
+        let vite = Vite::with_options(opts);
+        let mut env = Environment::new();
+        env.add_global("vite", Value::from_object(vite));
+        env.add_template("page.html", r#"{{ vite(resources="views/bar.js") }}"#)
+            .unwrap();
+        let result = env
+            .get_template("page.html")
+            .and_then(|template| template.render(Value::UNDEFINED))
+            .expect("Should work.");
+
+        assert!(result.starts_with("&lt;link rel=&quot;stylesheet&quot;"));
+        assert!(!result.contains('<'));
+    }
+
+    #[test]
+    fn can_minijinja_inject_base_tag() {
+        let opts = ViteOptions::default()
+            .mode(ViteMode::Production)
+            .base("/app/");
+
+        let vite = Vite::with_options(opts);
+        let vite_base_tag = ViteBaseTag::new(vite.base());
+        let mut env = Environment::new();
+        env.add_global("vite_base_tag", Value::from_object(vite_base_tag));
+        let result = env
+            .render_str(r#"{{ vite_base_tag() }}"#, Value::UNDEFINED)
+            .expect("Should work.");
+
+        assert_eq!(result, r#"<base href="/app/">"#);
+    }
+
+    #[test]
+    fn can_minijinja_inject_using_filter() {
+        let opts = ViteOptions::default()
+            .mode(ViteMode::Production)
+            .source(Some(SAMPLE_MANIFEST.to_string()));
+
+        let vite = Vite::with_options(opts);
+        let mut env = Environment::new();
+        vite.add_minijinja_filter(&mut env, "vite");
+        env.add_global("vite", Value::from_object(vite));
+        let result = env
+            .render_str(
+                r#"{{ "views/foo.js" | vite }}|{{ ["views/foo.js"] | vite }}|{{ vite(resources="views/foo.js") }}"#,
+                Value::UNDEFINED,
+            )
+            .expect("Should work.");
+
+        let expected = r#"<link rel="stylesheet" href="/assets/shared-ChJ_j-JJ.css" />
+<link rel="stylesheet" href="/assets/foo-5UjPuW-k.css" />
+<script type="module" src="/assets/foo-BRBmoGS9.js"></script>
+<link rel="modulepreload" href="/assets/shared-B7PI925R.js" />"#;
+
+        assert_eq!(result, [expected; 3].join("|"));
+    }
+
+    #[test]
+    fn can_minijinja_override_mode() {
+        let opts = ViteOptions::default()
+            .mode(ViteMode::Production)
+            .source(Some(SAMPLE_MANIFEST.to_string()));
+
+        let vite = Vite::with_options(opts);
+        let mut env = Environment::new();
+        env.add_global("vite", Value::from_object(vite));
+        let result = env
+            .render_str(
+                r#"{{ vite(resources=["views/foo.js"], mode="development") }}"#,
+                Value::UNDEFINED,
+            )
+            .expect("Should work.");
+
+        let expected = r#"<script type="module" src="http://localhost:5173/@vite/client"></script>
+<script type="module" src="http://localhost:5173/views/foo.js"></script>"#;
+
+        assert_eq!(result, expected);
+    }
+
+    #[test]
+    fn can_minijinja_prefetch() {
+        let opts = ViteOptions::default()
+            .mode(ViteMode::Production)
+            .source(Some(SAMPLE_MANIFEST.to_string()));
+
+        let vite = Vite::with_options(opts);
+        let mut env = Environment::new();
+        env.add_global(
+            "vite_prefetch",
+            Value::from_object(VitePrefetch::new(&vite)),
+        );
+        let result = env
+            .render_str(
+                r#"{{ vite_prefetch(resources="views/bar.js") }}"#,
+                Value::UNDEFINED,
+            )
+            .expect("Should work.");
+
+        let expected = r#"<link rel="prefetch" href="/assets/shared-ChJ_j-JJ.css" />
+<link rel="prefetch" href="/assets/bar-gkvgaI9m.js" />
+<link rel="prefetch" href="/assets/shared-B7PI925R.js" />"#;
+
+        assert_eq!(result, expected);
+    }
+
+    #[test]
+    fn minijinja_fails_without_manifest() {
+        let opts = ViteOptions::default()
+            .mode(ViteMode::Development)
+            .manifest_path("test/missing_manifest.json");
+
+        let vite = Vite::with_options(opts);
+        let mut env = Environment::new();
+        env.add_global("vite", Value::from_object(vite));
+        let result = env.render_str(
+            r#"{{ vite(resources=["views/foo.js"], mode="production") }}"#,
+            Value::UNDEFINED,
+        );
+
+        assert!(result.is_err());
+    }
 }
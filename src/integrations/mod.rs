@@ -5,3 +5,62 @@ pub mod tera;
 
 #[cfg(feature = "minijinja")]
 pub mod minijinja;
+
+#[cfg(feature = "leptos")]
+pub mod leptos;
+
+#[cfg(feature = "sailfish")]
+pub mod sailfish;
+
+#[cfg(feature = "dioxus")]
+pub mod dioxus;
+
+/// Splits a comma-separated list of entrypoints, e.g. `"app.js, admin.js"`,
+/// into its trimmed, non-empty parts.
+#[cfg(any(feature = "tera", feature = "minijinja"))]
+pub(crate) fn split_entrypoints(resources: &str) -> Vec<String> {
+    resources
+        .split(',')
+        .map(str::trim)
+        .filter(|entrypoint| !entrypoint.is_empty())
+        .map(str::to_string)
+        .collect()
+}
+
+/// Reads the entrypoints from the argument `resources` of a tera function,
+/// either an array or a comma-separated string. A missing argument is treated
+/// as an empty list of entrypoints.
+#[cfg(feature = "tera")]
+pub(crate) fn tera_entrypoints(
+    args: &std::collections::HashMap<String, ::tera::Value>,
+) -> ::tera::Result<Vec<String>> {
+    match args.get("resources") {
+        None => Ok(vec![]),
+        Some(resources) if resources.is_array() => Ok(::tera::from_value(resources.clone())?),
+        Some(resources) if resources.is_string() => {
+            Ok(split_entrypoints(&::tera::from_value::<String>(
+                resources.clone(),
+            )?))
+        }
+        Some(_) => {
+            Err("The argument 'resources' must be either a string or an array of strings.".into())
+        }
+    }
+}
+
+/// Reads the entrypoints from the keyword argument `resources` of a minijinja
+/// call, either a list or a comma-separated string. A missing argument is
+/// treated as an empty list of entrypoints.
+#[cfg(feature = "minijinja")]
+pub(crate) fn minijinja_entrypoints(
+    kwargs: &::minijinja::value::Kwargs,
+) -> Result<Vec<String>, ::minijinja::Error> {
+    let resources: Option<::minijinja::Value> = kwargs.get("resources")?;
+    match resources {
+        None => Ok(vec![]),
+        Some(resources) => match resources.as_str() {
+            Some(resources) => Ok(split_entrypoints(resources)),
+            None => kwargs.get("resources"),
+        },
+    }
+}
@@ -0,0 +1,96 @@
+//! This module implements the integration into sailfish, i.e. the type
+//! `SailfishTags`, which renders the tags required to include Vite's resources
+//! into a sailfish template.
+
+use crate::vite::Vite;
+
+use sailfish::runtime::{Buffer, Render, RenderError};
+
+/// Renders the tags required to include the given entrypoints into a sailfish
+/// template.
+///
+/// The tags are embedded verbatim, i.e. without being escaped, regardless of
+/// whether `<%= %>` or `<%- %>` is used.
+///
+/// # Examples
+///
+/// ```stpl
+/// <head>
+///     <%= vite.tags(&["app.js"]) %>
+/// </head>
+/// ```
+///
+pub struct SailfishTags<'a> {
+    vite: &'a Vite,
+    entrypoints: Vec<&'a str>,
+}
+
+impl Vite {
+    /// Returns the tags required to include the given entrypoints into a
+    /// sailfish template.
+    pub fn tags<'a>(&'a self, entrypoints: &[&'a str]) -> SailfishTags<'a> {
+        SailfishTags {
+            vite: self,
+            entrypoints: entrypoints.to_vec(),
+        }
+    }
+}
+
+impl Render for SailfishTags<'_> {
+    fn render(&self, b: &mut Buffer) -> Result<(), RenderError> {
+        let html = self
+            .vite
+            .to_html(self.entrypoints.clone())
+            .map_err(|err| RenderError::Msg(err.to_string()))?;
+
+        b.push_str(&html);
+        Ok(())
+    }
+
+    fn render_escaped(&self, b: &mut Buffer) -> Result<(), RenderError> {
+        self.render(b)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::vite::{Vite, ViteMode, ViteOptions};
+
+    use sailfish::runtime::{Buffer, Render};
+
+    const SAMPLE_MANIFEST: &str = include_str!("../../test/sample_manifest.json");
+
+    #[test]
+    fn can_sailfish_render_production() {
+        let opts = ViteOptions::default()
+            .mode(ViteMode::Production)
+            .source(Some(SAMPLE_MANIFEST));
+
+        let vite = Vite::with_options(opts);
+        let mut buffer = Buffer::new();
+        vite.tags(&["views/foo.js"])
+            .render_escaped(&mut buffer)
+            .unwrap();
+
+        let expected = r#"<link rel="stylesheet" href="/assets/shared-ChJ_j-JJ.css" />
+<link rel="stylesheet" href="/assets/foo-5UjPuW-k.css" />
+<script type="module" src="/assets/foo-BRBmoGS9.js"></script>
+<link rel="modulepreload" href="/assets/shared-B7PI925R.js" />"#;
+
+        assert_eq!(buffer.as_str(), expected);
+    }
+
+    #[test]
+    fn can_sailfish_render_development() {
+        let opts = ViteOptions::default().mode(ViteMode::Development);
+
+        let vite = Vite::with_options(opts);
+        let mut buffer = Buffer::new();
+        vite.tags(&["app.js"]).render_escaped(&mut buffer).unwrap();
+
+        let expected = r#"<script type="module" src="http://localhost:5173/@vite/client"></script>
+<script type="module" src="http://localhost:5173/app.js"></script>"#;
+
+        assert_eq!(buffer.as_str(), expected);
+    }
+}
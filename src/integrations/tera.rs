@@ -1,10 +1,11 @@
 //! This module implements the necessary traits required to make `crate::Vite`
 //! callable in tera templates.
 
-use crate::vite::{Vite, ViteReactRefresh};
+use crate::integrations::tera_entrypoints;
+use crate::vite::{Vite, ViteBaseTag, ViteDevBanner, ViteHmrUrl, VitePrefetch, ViteReactRefresh};
 
 use std::collections::HashMap;
-use tera::{from_value, to_value, Function, Result, Value};
+use tera::{from_value, to_value, Function, Result, Test, Value};
 
 /// Allows for instances of Vite to be bound as a function.
 ///
@@ -29,32 +30,69 @@ use tera::{from_value, to_value, Function, Result, Value};
 ///
 impl Function for Vite {
     fn is_safe(&self) -> bool {
-        true
+        self.mark_safe()
     }
 
     fn call(&self, args: &HashMap<String, Value>) -> Result<Value> {
-        let Some(resources) = args.get("resources") else {
-            return Err("Missing argument 'resources' in vite function.".into());
-        };
+        let entrypoints = tera_entrypoints(args)?;
 
-        let entrypoints: Vec<String>;
-        if resources.is_array() {
-            entrypoints = from_value(resources.clone())?;
-        } else if resources.is_string() {
-            entrypoints = vec![from_value(resources.clone())?];
-        } else {
-            return Err(
-                "The argument 'resources' must be either a string or an array of strings.".into(),
-            );
-        }
+        // The mode may be overridden per call, e.g. to force development tags.
+        let mode = match args.get("mode") {
+            None => None,
+            Some(mode) => match from_value::<String>(mode.clone())?.parse() {
+                Ok(mode) => Some(mode),
+                Err(err) => return Err(tera::Error::msg(err)),
+            },
+        };
 
         let entrypoints = entrypoints.iter().map(|e| e.as_str()).collect();
-        let code = self.to_html(entrypoints).unwrap();
+        let code = match mode {
+            Some(mode) => self.to_html_with_mode(entrypoints, mode),
+            None => self.to_html(entrypoints),
+        }
+        .map_err(|err| tera::Error::msg(err.to_string()))?;
 
         Ok(to_value(code)?)
     }
 }
 
+/// Allows for instances of Vite to be bound as a test, which checks whether
+/// an entrypoint includes any stylesheet.
+///
+/// # Examples
+///
+/// ```
+/// use in_vite::Vite;
+/// use tera::{Tera, Context, Result};
+///
+/// fn main() -> Result<()> {
+///     let vite = Vite::default();
+///     let mut tera = Tera::default();
+///     tera.register_function("vite", vite.clone());
+///     tera.register_tester("vite_has_css", vite);
+///
+///     let ctx = Context::new();
+///     let template = tera.render_str(
+///         r#"{% set entry = "app.js" %}{% if entry is vite_has_css %}...{% endif %}"#,
+///         &ctx,
+///     );
+///
+///     Ok(())
+/// }
+///
+/// ```
+///
+impl Test for Vite {
+    fn test(&self, value: Option<&Value>, _args: &[Value]) -> Result<bool> {
+        let Some(entry) = value.and_then(|value| value.as_str()) else {
+            return Err("The tested value must be an entrypoint string.".into());
+        };
+
+        self.entry_has_css(entry)
+            .map_err(|err| tera::Error::msg(err.to_string()))
+    }
+}
+
 /// Allows for instances of ViteReactRefresh to be bound as a function.
 ///
 /// # Examples
@@ -87,11 +125,145 @@ impl Function for ViteReactRefresh {
     }
 }
 
+/// Allows for instances of ViteDevBanner to be bound as a function.
+///
+/// # Examples
+///
+/// ```
+/// use in_vite::{Vite, ViteDevBanner};
+/// use tera::{Tera, Context, Result};
+///
+/// fn main() -> Result<()> {
+///     let vite = Vite::default();
+///     let mut tera = Tera::default();
+///     let vite_dev_banner = ViteDevBanner::new(vite.host(), vite.mode());
+///     tera.register_function("vite_dev_banner", vite_dev_banner);
+///
+///     let ctx = Context::new();
+///     let template = tera.render_str(r#"{{ vite_dev_banner() }}"#, &ctx);
+///
+///     Ok(())
+/// }
+///
+/// ```
+///
+impl Function for ViteDevBanner {
+    fn is_safe(&self) -> bool {
+        true
+    }
+
+    fn call(&self, _args: &HashMap<String, Value>) -> Result<Value> {
+        Ok(to_value(self.dev_banner_html())?)
+    }
+}
+
+/// Allows for instances of ViteHmrUrl to be bound as a function.
+///
+/// # Examples
+///
+/// ```
+/// use in_vite::{Vite, ViteHmrUrl};
+/// use tera::{Tera, Context, Result};
+///
+/// fn main() -> Result<()> {
+///     let vite = Vite::default();
+///     let mut tera = Tera::default();
+///     let vite_hmr_url = ViteHmrUrl::new(vite.host(), vite.mode());
+///     tera.register_function("vite_hmr_url", vite_hmr_url);
+///
+///     let ctx = Context::new();
+///     let template = tera.render_str(r#"{{ vite_hmr_url() }}"#, &ctx);
+///
+///     Ok(())
+/// }
+///
+/// ```
+///
+impl Function for ViteHmrUrl {
+    fn is_safe(&self) -> bool {
+        true
+    }
+
+    fn call(&self, _args: &HashMap<String, Value>) -> Result<Value> {
+        Ok(to_value(self.hmr_url())?)
+    }
+}
+
+/// Allows for instances of ViteBaseTag to be bound as a function.
+///
+/// # Examples
+///
+/// ```
+/// use in_vite::{Vite, ViteBaseTag};
+/// use tera::{Tera, Context, Result};
+///
+/// fn main() -> Result<()> {
+///     let vite = Vite::default();
+///     let mut tera = Tera::default();
+///     let vite_base_tag = ViteBaseTag::new(vite.base());
+///     tera.register_function("vite_base_tag", vite_base_tag);
+///
+///     let ctx = Context::new();
+///     let template = tera.render_str(r#"{{ vite_base_tag() }}"#, &ctx);
+///
+///     Ok(())
+/// }
+///
+/// ```
+///
+impl Function for ViteBaseTag {
+    fn is_safe(&self) -> bool {
+        true
+    }
+
+    fn call(&self, _args: &HashMap<String, Value>) -> Result<Value> {
+        Ok(to_value(self.base_tag())?)
+    }
+}
+
+/// Allows for instances of VitePrefetch to be bound as a function.
+///
+/// # Examples
+///
+/// ```
+/// use in_vite::{Vite, VitePrefetch};
+/// use tera::{Tera, Context, Result};
+///
+/// fn main() -> Result<()> {
+///     let vite = Vite::default();
+///     let mut tera = Tera::default();
+///     tera.register_function("vite_prefetch", VitePrefetch::new(&vite));
+///
+///     let ctx = Context::new();
+///     let template = tera.render_str(r#"{{ vite_prefetch(resources="app.js") }}"#, &ctx);
+///
+///     Ok(())
+/// }
+///
+/// ```
+///
+impl Function for VitePrefetch {
+    fn is_safe(&self) -> bool {
+        true
+    }
+
+    fn call(&self, args: &HashMap<String, Value>) -> Result<Value> {
+        let entrypoints = tera_entrypoints(args)?;
+
+        let entrypoints = entrypoints.iter().map(|e| e.as_str()).collect();
+        let code = self
+            .prefetch(entrypoints)
+            .map_err(|err| tera::Error::msg(err.to_string()))?;
+
+        Ok(to_value(code)?)
+    }
+}
+
 #[cfg(test)]
 mod test {
     use crate::vite::{ViteMode, ViteOptions};
 
-    use super::{Vite, ViteReactRefresh};
+    use super::{Vite, ViteBaseTag, ViteDevBanner, ViteHmrUrl, VitePrefetch, ViteReactRefresh};
 
     const SAMPLE_MANIFEST: &str = include_str!("../../test/sample_manifest.json");
 
@@ -109,7 +281,7 @@ mod test {
         let expected = r#"<script type="module" src="http://localhost:5173/@vite/client"></script>
 <script type="module" src="http://localhost:5173/app.js"></script>"#;
 
-        assert!(matches!(result, Ok(_)));
+        assert!(result.is_ok());
         assert_eq!(result.unwrap(), expected);
     }
 
@@ -128,15 +300,56 @@ mod test {
             &tera::Context::new(),
         );
 
-        let expected = r#"<link rel="stylesheet" href="assets/foo-5UjPuW-k.css" />
-<link rel="stylesheet" href="assets/shared-ChJ_j-JJ.css" />
-<script type="module" src="assets/foo-BRBmoGS9.js"></script>
-<link rel="modulepreload" href="assets/shared-B7PI925R.js" />"#;
+        let expected = r#"<link rel="stylesheet" href="/assets/shared-ChJ_j-JJ.css" />
+<link rel="stylesheet" href="/assets/foo-5UjPuW-k.css" />
+<script type="module" src="/assets/foo-BRBmoGS9.js"></script>
+<link rel="modulepreload" href="/assets/shared-B7PI925R.js" />"#;
 
-        assert!(matches!(result, Ok(_)));
+        assert!(result.is_ok());
         assert_eq!(result.unwrap(), expected);
     }
 
+    #[test]
+    fn can_tera_inject_comma_separated_resources() {
+        let opts = ViteOptions::default()
+            .mode(ViteMode::Development)
+            .source(Some(SAMPLE_MANIFEST.to_string()));
+
+        let vite = Vite::with_options(opts);
+        let mut tera = tera::Tera::default();
+
+        tera.register_function("vite", vite);
+        let result = tera.render_str(
+            r#"{{ vite(resources="app.js, admin.js") }}"#,
+            &tera::Context::new(),
+        );
+        let expected = r#"<script type="module" src="http://localhost:5173/@vite/client"></script>
+<script type="module" src="http://localhost:5173/app.js"></script>
+<script type="module" src="http://localhost:5173/admin.js"></script>"#;
+
+        assert!(result.is_ok());
+        assert_eq!(result.unwrap(), expected);
+    }
+
+    #[test]
+    fn can_tera_test_entry_for_css() {
+        let opts = ViteOptions::default()
+            .mode(ViteMode::Production)
+            .source(Some(SAMPLE_MANIFEST.to_string()));
+
+        let vite = Vite::with_options(opts);
+        let mut tera = tera::Tera::default();
+
+        tera.register_tester("vite_has_css", vite);
+        let result = tera.render_str(
+            r#"{% for entry in ["views/foo.js", "baz.js"] %}{% if entry is vite_has_css %}{{ entry }}{% endif %}{% endfor %}"#,
+            &tera::Context::new(),
+        );
+
+        assert!(result.is_ok());
+        assert_eq!(result.unwrap(), "views/foo.js");
+    }
+
     #[test]
     fn can_tera_inject_react_refresh_development() {
         let opts = ViteOptions::default()
@@ -157,7 +370,7 @@ window.$RefreshSig$ = () => (type) => type
 window.__vite_plugin_react_preamble_installed__ = true
 </script>"#;
 
-        assert!(matches!(result, Ok(_)));
+        assert!(result.is_ok());
         assert_eq!(result.unwrap(), expected);
     }
 
@@ -175,7 +388,166 @@ window.__vite_plugin_react_preamble_installed__ = true
         let result = tera.render_str(r#"{{ vite_react_refresh() }}"#, &tera::Context::new());
         let expected = "";
 
-        assert!(matches!(result, Ok(_)));
+        assert!(result.is_ok());
         assert_eq!(result.unwrap(), expected);
     }
+
+    #[test]
+    fn can_tera_inject_dev_banner_development() {
+        let opts = ViteOptions::default().mode(ViteMode::Development);
+
+        let vite = Vite::with_options(opts);
+        let vite_dev_banner = ViteDevBanner::new(vite.host(), vite.mode());
+        let mut tera = tera::Tera::default();
+
+        tera.register_function("vite_dev_banner", vite_dev_banner);
+        let result = tera.render_str(r#"{{ vite_dev_banner() }}"#, &tera::Context::new());
+
+        let html = result.unwrap();
+        assert!(html.contains(r#"<div id="in-vite-dev-banner" hidden"#));
+        assert!(html.contains(r#"import("http://localhost:5173/@vite/client")"#));
+    }
+
+    #[test]
+    fn tera_injects_nothing_dev_banner_production() {
+        let opts = ViteOptions::default().mode(ViteMode::Production);
+
+        let vite = Vite::with_options(opts);
+        let vite_dev_banner = ViteDevBanner::new(vite.host(), vite.mode());
+        let mut tera = tera::Tera::default();
+
+        tera.register_function("vite_dev_banner", vite_dev_banner);
+        let result = tera.render_str(r#"{{ vite_dev_banner() }}"#, &tera::Context::new());
+
+        assert!(result.is_ok());
+        assert_eq!(result.unwrap(), "");
+    }
+
+    #[test]
+    fn tera_treats_missing_resources_as_empty() {
+        let opts = ViteOptions::default().mode(ViteMode::Development);
+
+        let vite = Vite::with_options(opts);
+        let mut tera = tera::Tera::default();
+
+        tera.register_function("vite", vite);
+        let result = tera.render_str(r#"{{ vite() }}"#, &tera::Context::new());
+        let expected =
+            r#"<script type="module" src="http://localhost:5173/@vite/client"></script>"#;
+
+        assert!(result.is_ok());
+        assert_eq!(result.unwrap(), expected);
+    }
+
+    #[test]
+    fn can_tera_inject_hmr_url_development() {
+        let opts = ViteOptions::default().mode(ViteMode::Development);
+
+        let vite = Vite::with_options(opts);
+        let vite_hmr_url = ViteHmrUrl::new(vite.host(), vite.mode());
+        let mut tera = tera::Tera::default();
+
+        tera.register_function("vite_hmr_url", vite_hmr_url);
+        let result = tera.render_str(r#"{{ vite_hmr_url() }}"#, &tera::Context::new());
+
+        assert!(result.is_ok());
+        assert_eq!(result.unwrap(), "ws://localhost:5173/");
+    }
+
+    #[test]
+    fn tera_escapes_unless_marked_safe() {
+        let opts = ViteOptions::default()
+            .mode(ViteMode::Production)
+            .mark_safe(false)
+            .source(Some(SAMPLE_MANIFEST.to_string()));
+
+        let vite = Vite::with_options(opts);
+        let mut tera = tera::Tera::default();
+
+        tera.register_function("vite", vite);
+        tera.add_raw_template("page.html", r#"{{ vite(resources="views/bar.js") }}"#)
+            .unwrap();
+        let result = tera.render("page.html", &tera::Context::new()).unwrap();
+
+        assert!(result.starts_with("&lt;link rel=&quot;stylesheet&quot;"));
+        assert!(!result.contains('<'));
+    }
+
+    #[test]
+    fn can_tera_inject_base_tag() {
+        let opts = ViteOptions::default()
+            .mode(ViteMode::Production)
+            .base("/app/");
+
+        let vite = Vite::with_options(opts);
+        let vite_base_tag = ViteBaseTag::new(vite.base());
+        let mut tera = tera::Tera::default();
+
+        tera.register_function("vite_base_tag", vite_base_tag);
+        let result = tera.render_str(r#"{{ vite_base_tag() }}"#, &tera::Context::new());
+
+        assert!(result.is_ok());
+        assert_eq!(result.unwrap(), r#"<base href="/app/">"#);
+    }
+
+    #[test]
+    fn can_tera_override_mode() {
+        let opts = ViteOptions::default()
+            .mode(ViteMode::Production)
+            .source(Some(SAMPLE_MANIFEST.to_string()));
+
+        let vite = Vite::with_options(opts);
+        let mut tera = tera::Tera::default();
+
+        tera.register_function("vite", vite);
+        let result = tera.render_str(
+            r#"{{ vite(resources="views/foo.js", mode="development") }}"#,
+            &tera::Context::new(),
+        );
+
+        let expected = r#"<script type="module" src="http://localhost:5173/@vite/client"></script>
+<script type="module" src="http://localhost:5173/views/foo.js"></script>"#;
+
+        assert_eq!(result.unwrap(), expected);
+    }
+
+    #[test]
+    fn can_tera_prefetch() {
+        let opts = ViteOptions::default()
+            .mode(ViteMode::Production)
+            .source(Some(SAMPLE_MANIFEST.to_string()));
+
+        let vite = Vite::with_options(opts);
+        let mut tera = tera::Tera::default();
+
+        tera.register_function("vite_prefetch", VitePrefetch::new(&vite));
+        let result = tera.render_str(
+            r#"{{ vite_prefetch(resources=["views/bar.js"]) }}"#,
+            &tera::Context::new(),
+        );
+
+        let expected = r#"<link rel="prefetch" href="/assets/shared-ChJ_j-JJ.css" />
+<link rel="prefetch" href="/assets/bar-gkvgaI9m.js" />
+<link rel="prefetch" href="/assets/shared-B7PI925R.js" />"#;
+
+        assert_eq!(result.unwrap(), expected);
+    }
+
+    #[test]
+    fn tera_fails_without_manifest() {
+        let opts = ViteOptions::default()
+            .mode(ViteMode::Development)
+            .manifest_path("test/missing_manifest.json");
+
+        let vite = Vite::with_options(opts);
+        let mut tera = tera::Tera::default();
+
+        tera.register_function("vite", vite);
+        let result = tera.render_str(
+            r#"{{ vite(resources="views/foo.js", mode="production") }}"#,
+            &tera::Context::new(),
+        );
+
+        assert!(result.is_err());
+    }
 }
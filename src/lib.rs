@@ -4,7 +4,24 @@
 mod error;
 mod integrations;
 mod manifest;
+pub mod prelude;
 mod resource;
 mod vite;
 
-pub use vite::{Vite, ViteMode, ViteOptions, ViteReactRefresh};
+pub use error::Error;
+pub use manifest::{
+    validate_manifest, Chunk, ChunkMetadata, Manifest, ManifestDiff, ManifestFormat,
+    ResolutionReport, ResolutionStatus,
+};
+pub use resource::{render_tags, AssetKind, ResolvedAsset, TagOptions};
+pub use vite::{
+    Vite, ViteBaseTag, ViteCrossOrigin, ViteCssMode, ViteDevBanner, ViteHmrUrl, ViteMode,
+    ViteOptions, VitePrefetch, ViteReactRefresh, ViteReactRefreshOptions, ViteStylesheetOptions,
+    ViteTags, REACT_REFRESH_PREAMBLE,
+};
+
+#[cfg(feature = "leptos")]
+pub use integrations::leptos::ViteAssets;
+
+#[cfg(feature = "sailfish")]
+pub use integrations::sailfish::SailfishTags;
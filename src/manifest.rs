@@ -7,65 +7,673 @@
 //!             https://github.com/vitejs/vite/discussions/11546
 //!
 
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
+use std::io::{BufRead, BufReader, Read};
+use std::path::Path;
+use std::sync::Arc;
 
-use crate::resource::Resource;
+use crate::error::Error;
+use crate::resource::{preload_destination, Resource};
 
-#[allow(dead_code)]
-#[derive(serde::Deserialize)]
+/// Number of bytes of non-JSON content included in `Error::ManifestNotJson`.
+const NOT_JSON_SNIPPET_LEN: u64 = 64;
+
+/// Skips leading whitespace of `reader` and fails with a snippet of the
+/// content unless it continues with `{`.
+fn expect_json_object<R: Read>(reader: &mut BufReader<R>) -> Result<(), Error> {
+    loop {
+        let buf = reader.fill_buf()?;
+        let Some(&first) = buf.first() else {
+            // Leaves reporting empty content to the deserializer.
+            return Ok(());
+        };
+
+        let whitespace = buf.iter().take_while(|b| b.is_ascii_whitespace()).count();
+        if whitespace == 0 {
+            if first == b'{' {
+                return Ok(());
+            }
+
+            let mut snippet = Vec::new();
+            reader
+                .take(NOT_JSON_SNIPPET_LEN)
+                .read_to_end(&mut snippet)?;
+            let snippet = String::from_utf8_lossy(&snippet).into_owned();
+            return Err(Error::ManifestNotJson(snippet));
+        }
+
+        reader.consume(whitespace);
+    }
+}
+
+/// Enumerates the formats a manifest can be deserialized from.
+#[derive(Default, Debug, Clone, PartialEq, Eq)]
+pub enum ManifestFormat {
+    /// Vite's native manifest format.
+    #[default]
+    Json,
+
+    /// A manifest converted to YAML. Requires the feature `yaml`.
+    #[cfg(feature = "yaml")]
+    Yaml,
+
+    /// A manifest converted to TOML. Requires the feature `toml`.
+    #[cfg(feature = "toml")]
+    Toml,
+}
+
+impl ManifestFormat {
+    /// Deserializes the manifest from `source` using this format.
+    ///
+    /// If a JSON `pointer` is given, the manifest is deserialized from the
+    /// value it points to instead of the whole document.
+    pub(crate) fn deserialize(
+        &self,
+        source: &str,
+        pointer: Option<&str>,
+    ) -> Result<Manifest, Error> {
+        let Some(pointer) = pointer else {
+            return self.deserialize_value(source);
+        };
+
+        let mut document: serde_json::Value = self.deserialize_value(source)?;
+        let Some(manifest) = document.pointer_mut(pointer) else {
+            return Err(Error::ManifestPointer(pointer.to_string()));
+        };
+
+        Ok(serde_json::from_value(manifest.take())?)
+    }
+
+    /// Deserializes the manifest from `reader` using this format.
+    ///
+    /// JSON not starting with an object, e.g. an HTML error page served in
+    /// place of the manifest, is rejected with `Error::ManifestNotJson`.
+    pub(crate) fn deserialize_reader<R: Read>(
+        &self,
+        reader: R,
+        pointer: Option<&str>,
+    ) -> Result<Manifest, Error> {
+        let mut reader = BufReader::new(reader);
+        if *self == Self::Json {
+            expect_json_object(&mut reader)?;
+        }
+
+        if let (Self::Json, None) = (self, pointer) {
+            return Ok(serde_json::from_reader(reader)?);
+        }
+
+        let mut source = String::new();
+        reader.read_to_string(&mut source)?;
+        self.deserialize(&source, pointer)
+    }
+
+    pub(crate) fn deserialize_value<T: serde::de::DeserializeOwned>(
+        &self,
+        source: &str,
+    ) -> Result<T, Error> {
+        let value = match self {
+            Self::Json => serde_json::from_str(source)?,
+            #[cfg(feature = "yaml")]
+            Self::Yaml => serde_yaml::from_str(source)?,
+            #[cfg(feature = "toml")]
+            Self::Toml => toml::from_str(source)?,
+        };
+
+        Ok(value)
+    }
+}
+
+/// Validates the manifest at `path` by parsing it and returns the number of
+/// entry chunks it contains, e.g. to fail early on a broken build.
+///
+/// The format is guessed from the extension of `path`, defaulting to JSON.
+pub fn validate_manifest<P: AsRef<Path>>(path: P) -> Result<usize, Error> {
+    let path = path.as_ref();
+    let format = match path.extension().and_then(|ext| ext.to_str()) {
+        #[cfg(feature = "yaml")]
+        Some("yaml" | "yml") => ManifestFormat::Yaml,
+        #[cfg(feature = "toml")]
+        Some("toml") => ManifestFormat::Toml,
+        _ => ManifestFormat::Json,
+    };
+
+    let source = std::fs::read_to_string(path)?;
+    let manifest = format.deserialize(&source, None)?;
+
+    Ok(manifest.0.values().filter(|chunk| chunk.is_entry).count())
+}
+
+/// Enumerates the outcomes of resolving an entrypoint, as reported by
+/// `Vite::diagnose`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ResolutionStatus {
+    /// The entrypoint resolved to the given number of resources.
+    Resolved(usize),
+
+    /// The manifest lacks a chunk for the entrypoint.
+    NotFound,
+
+    /// The chunk of the entrypoint is not marked as entry and thus skipped.
+    NotEntry,
+
+    /// The chunk of the entrypoint, or one of its imports, imports chunks
+    /// lacking from the manifest.
+    MissingImports(Vec<String>),
+}
+
+/// Reports how each of the diagnosed entrypoints resolved, in the order they
+/// were passed.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ResolutionReport {
+    /// The entrypoints along with their status.
+    pub entrypoints: Vec<(String, ResolutionStatus)>,
+}
+
+impl ResolutionReport {
+    /// Returns the status of the given entrypoint, if it was diagnosed.
+    pub fn status(&self, entrypoint: &str) -> Option<&ResolutionStatus> {
+        self.entrypoints
+            .iter()
+            .find(|(entry, _)| entry == entrypoint)
+            .map(|(_, status)| status)
+    }
+}
+
+/// Describes a chunk of the manifest, e.g. to enforce size budgets.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ChunkMetadata {
+    /// The file the chunk was bundled into.
+    pub file: String,
+
+    /// The size of the file in bytes, if reported.
+    pub size: Option<u64>,
+
+    /// The size of the gzip-compressed file in bytes, if reported.
+    pub gzip_size: Option<u64>,
+}
+
+/// Lists the files which changed between two builds, as returned by
+/// `Vite::diff_manifest`, e.g. to precache them in a service worker.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ManifestDiff {
+    /// The URLs of the files of chunks lacking from the previous manifest.
+    pub added: Vec<String>,
+
+    /// The URLs of the files of chunks lacking from the current manifest.
+    pub removed: Vec<String>,
+
+    /// The URLs of the files of chunks bundled into a different file than
+    /// previously, i.e. their current files.
+    pub changed: Vec<String>,
+}
+
+/// Provides a fresh reader over the manifest whenever it is loaded.
+#[derive(Clone)]
+pub(crate) struct ManifestReader(pub Arc<dyn Fn() -> Result<Box<dyn Read>, Error> + Send + Sync>);
+
+impl std::fmt::Debug for ManifestReader {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str("ManifestReader")
+    }
+}
+
+/// Options controlling how resources are resolved from the manifest.
+pub(crate) struct ResolveOptions {
+    /// Whether stylesheets of dynamically imported chunks are included.
+    pub dynamic_import_css: bool,
+
+    /// Whether stylesheets of imported chunks are preloaded instead of
+    /// linked.
+    pub preload_import_css: bool,
+
+    /// Whether the module of the entrypoint is preloaded instead of executed.
+    pub entry_as_preload: bool,
+
+    /// Whether chunks not flagged as entry can be resolved as entrypoint.
+    pub allow_non_entry: bool,
+
+    /// Whether imported chunks are preloaded.
+    pub emit_preloads: bool,
+
+    /// Whether assets referenced by chunks are preloaded.
+    pub preload_assets: bool,
+
+    /// The key of the combined stylesheet included along with every script
+    /// entrypoint, if CSS code splitting is disabled.
+    pub single_css: Option<String>,
+
+    /// Whether entrypoints are matched against the keys of the manifest
+    /// regardless of their case.
+    pub case_insensitive: bool,
+}
+
+impl Default for ResolveOptions {
+    fn default() -> Self {
+        Self {
+            dynamic_import_css: false,
+            preload_import_css: false,
+            entry_as_preload: false,
+            allow_non_entry: false,
+            emit_preloads: true,
+            preload_assets: false,
+            single_css: None,
+            case_insensitive: false,
+        }
+    }
+}
+
+/// Represents Vite's build manifest, mapping the keys of chunks, e.g. the
+/// sources of entrypoints, to the chunks. Obtained using `Vite::with_manifest`.
+#[derive(serde::Deserialize, Debug)]
 #[serde(transparent)]
-pub(crate) struct Manifest(HashMap<String, Chunk>);
+pub struct Manifest(HashMap<String, Chunk>);
 
-#[allow(dead_code)]
-#[derive(serde::Deserialize)]
+/// Maps the IDs of modules to the files, prefixed with the base, they've been
+/// bundled into, as emitted by `vite build --ssrManifest`.
+#[derive(serde::Deserialize, Debug)]
+#[serde(transparent)]
+pub(crate) struct SsrManifest(HashMap<String, Vec<String>>);
+
+impl SsrManifest {
+    /// Returns a deduplicated, sorted list of resources to preload for the
+    /// given modules. Unknown modules are skipped.
+    pub fn resolve_resources<'a>(&'a self, module_ids: &[&str]) -> Vec<Resource<'a>> {
+        let mut resources: Vec<Resource<'a>> = module_ids
+            .iter()
+            .filter_map(|id| self.0.get(*id))
+            .flatten()
+            .filter_map(|file| {
+                if file.ends_with(".css") {
+                    Some(Resource::Stylesheet(file))
+                } else if file.ends_with(".js") || file.ends_with(".mjs") {
+                    Some(Resource::PreloadModule(file))
+                } else {
+                    preload_destination(file).map(|_| Resource::PreloadAsset(file))
+                }
+            })
+            .collect();
+
+        resources.sort();
+        resources.dedup();
+        resources
+    }
+}
+
+/// Represents a chunk of the manifest.
+#[derive(serde::Deserialize, Debug)]
 #[serde(rename_all = "camelCase")]
-pub(crate) struct Chunk {
+pub struct Chunk {
+    /// The source the chunk was bundled from, if any.
     #[serde(default)]
     pub src: Option<String>,
 
+    /// The file the chunk was bundled into.
     pub file: String,
 
+    /// The name of the chunk, i.e. its file without hash and extension, if
+    /// reported.
+    #[serde(default)]
+    pub name: Option<String>,
+
+    /// The stylesheets imported by the chunk.
     #[serde(default)]
     pub css: Vec<String>,
 
+    /// The assets, e.g. fonts or images, referenced by the chunk.
     #[serde(default)]
     pub assets: Vec<String>,
 
+    /// Whether the chunk is an entrypoint.
     #[serde(default)]
     pub is_entry: bool,
 
+    /// Whether the chunk is imported dynamically.
     #[serde(default)]
     pub is_dynamic_entry: bool,
 
+    /// The keys of the chunks imported statically.
     #[serde(default)]
     pub imports: Vec<String>,
 
+    /// The keys of the chunks imported dynamically.
     #[serde(default)]
     pub dynamic_imports: Vec<String>,
+
+    /// The format of the chunk, e.g. `es` or `umd`, if reported.
+    #[serde(default)]
+    pub format: Option<String>,
+
+    /// The size of the file in bytes, if reported.
+    #[serde(default)]
+    pub size: Option<u64>,
+
+    /// The size of the gzip-compressed file in bytes, if reported.
+    #[serde(default)]
+    pub gzip_size: Option<u64>,
+}
+
+impl Chunk {
+    /// Returns the metadata of the chunk.
+    pub fn metadata(&self) -> ChunkMetadata {
+        ChunkMetadata {
+            file: self.file.clone(),
+            size: self.size,
+            gzip_size: self.gzip_size,
+        }
+    }
+
+    /// Returns the logical name of the chunk, i.e. `name` or, if unreported,
+    /// its file stripped of directory, hash and extension, e.g. `foo` for
+    /// `assets/foo-BRBmoGS9.js`.
+    pub fn logical_name(&self) -> &str {
+        if let Some(name) = &self.name {
+            return name;
+        }
+
+        let file = self.file.rsplit('/').next().unwrap_or(&self.file);
+        let stem = file.split_once('.').map_or(file, |(stem, _)| stem);
+
+        // Vite appends a dash and a hash of eight characters, which itself
+        // may contain dashes, e.g. `shared-ChJ_j-JJ`.
+        match stem.len().checked_sub(9) {
+            Some(dash) if dash > 0 && stem.as_bytes()[dash] == b'-' => &stem[..dash],
+            _ => stem,
+        }
+    }
+
+    /// Returns whether the chunk must be loaded as classic script instead of
+    /// an ES module, e.g. for library builds in UMD or IIFE format.
+    pub fn is_classic_script(&self) -> bool {
+        if let Some(format) = &self.format {
+            return format != "es";
+        }
+
+        self.file.ends_with(".cjs")
+            || self.file.ends_with(".umd.js")
+            || self.file.ends_with(".iife.js")
+    }
 }
 
 impl<'a> Manifest {
+    /// Returns the chunk with the given key, if any.
+    pub fn chunk(&'a self, key: &str) -> Option<&'a Chunk> {
+        self.0.get(key)
+    }
+
+    /// Returns an iterator over the keys and chunks in arbitrary order.
+    pub fn iter(&'a self) -> impl Iterator<Item = (&'a str, &'a Chunk)> {
+        self.0.iter().map(|(key, chunk)| (key.as_str(), chunk))
+    }
+
+    /// Returns the number of chunks.
+    pub fn len(&self) -> usize {
+        self.0.len()
+    }
+
+    /// Returns whether the manifest contains no chunks.
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+
+    /// Merges the chunks of `other` into this manifest, keeping chunks already
+    /// present.
+    pub(crate) fn merge(&mut self, other: Manifest) {
+        for (key, chunk) in other.0 {
+            self.0.entry(key).or_insert(chunk);
+        }
+    }
+
+    /// Returns the sorted, deduplicated files of all stylesheets across all
+    /// chunks.
+    pub fn stylesheets(&'a self) -> Vec<&'a str> {
+        let mut stylesheets: Vec<&'a str> = self
+            .0
+            .values()
+            .flat_map(|chunk| {
+                let file = Some(chunk.file.as_str()).filter(|file| file.ends_with(".css"));
+                chunk.css.iter().map(String::as_str).chain(file)
+            })
+            .collect();
+
+        stylesheets.sort();
+        stylesheets.dedup();
+        stylesheets
+    }
+
+    /// Compares the files of the chunks against those of the `previous`
+    /// manifest, returning the sorted files of added, removed and changed
+    /// chunks.
+    pub(crate) fn diff(
+        &'a self,
+        previous: &'a Manifest,
+    ) -> (Vec<&'a str>, Vec<&'a str>, Vec<&'a str>) {
+        let mut added = vec![];
+        let mut changed = vec![];
+        for (key, chunk) in self.0.iter() {
+            match previous.chunk(key) {
+                None => added.push(chunk.file.as_str()),
+                Some(old) if old.file != chunk.file => changed.push(chunk.file.as_str()),
+                Some(_) => {}
+            }
+        }
+
+        let mut removed: Vec<&str> = previous
+            .0
+            .iter()
+            .filter(|(key, _)| !self.0.contains_key(*key))
+            .map(|(_, chunk)| chunk.file.as_str())
+            .collect();
+
+        for files in [&mut added, &mut removed, &mut changed] {
+            files.sort();
+            files.dedup();
+        }
+
+        (added, removed, changed)
+    }
+
+    /// Returns the sorted files of all chunks lacking a source, imports and
+    /// entry flags, which are neither scripts nor stylesheets, i.e. assets
+    /// like fonts or images referenced only by `url()` within stylesheets.
+    pub fn orphan_assets(&'a self) -> Vec<&'a str> {
+        let mut assets: Vec<&'a str> = self
+            .0
+            .values()
+            .filter(|chunk| {
+                chunk.src.is_none()
+                    && chunk.imports.is_empty()
+                    && !chunk.is_entry
+                    && !chunk.is_dynamic_entry
+            })
+            .map(|chunk| chunk.file.as_str())
+            .filter(|file| {
+                ![".js", ".mjs", ".cjs", ".css"]
+                    .iter()
+                    .any(|extension| file.ends_with(extension))
+            })
+            .collect();
+
+        assets.sort();
+        assets.dedup();
+        assets
+    }
+
+    /// Returns whether any chunk reports its name, which Vite does as of its
+    /// fifth major version.
+    pub(crate) fn has_chunk_names(&self) -> bool {
+        self.0.values().any(|chunk| chunk.name.is_some())
+    }
+
+    /// Returns the key of the chunk the given file belongs to, i.e. the chunk
+    /// bundled into it or listing it as stylesheet or asset. Should several
+    /// chunks list the file, the first key in order is returned.
+    pub fn chunk_of(&'a self, file: &str) -> Option<&'a str> {
+        self.0
+            .iter()
+            .filter(|(_, chunk)| {
+                chunk.file == file
+                    || chunk.css.iter().any(|css| css == file)
+                    || chunk.assets.iter().any(|asset| asset == file)
+            })
+            .map(|(key, _)| key.as_str())
+            .min()
+    }
+
+    /// Returns the key and chunk of the given entrypoint, lowercasing both for
+    /// matching if `case_insensitive` is enabled and the exact key is unknown.
+    pub(crate) fn entry_chunk(
+        &'a self,
+        entrypoint: &'a str,
+        opts: &ResolveOptions,
+    ) -> Option<(&'a str, &'a Chunk)> {
+        if let Some(chunk) = self.0.get(entrypoint) {
+            return Some((entrypoint, chunk));
+        }
+
+        if !opts.case_insensitive {
+            return None;
+        }
+
+        let entrypoint = entrypoint.to_lowercase();
+        self.0
+            .iter()
+            .filter(|(key, _)| key.to_lowercase() == entrypoint)
+            .map(|(key, chunk)| (key.as_str(), chunk))
+            .min_by_key(|(key, _)| *key)
+    }
+
+    /// Diagnoses how the given entrypoint resolves.
+    pub(crate) fn diagnose(
+        &'a self,
+        entrypoint: &'a str,
+        opts: &ResolveOptions,
+    ) -> ResolutionStatus {
+        let Some((entrypoint, chunk)) = self.entry_chunk(entrypoint, opts) else {
+            return ResolutionStatus::NotFound;
+        };
+
+        if !chunk.is_entry && !opts.allow_non_entry {
+            return ResolutionStatus::NotEntry;
+        }
+
+        let mut missing = vec![];
+        let mut visited = HashSet::new();
+        self.find_missing_imports(&mut missing, &mut visited, chunk);
+        if !missing.is_empty() {
+            missing.sort();
+            missing.dedup();
+            return ResolutionStatus::MissingImports(missing);
+        }
+
+        ResolutionStatus::Resolved(self.resolve_resources(entrypoint, opts).len())
+    }
+
+    /// Recursively walks the static imports of `chunk` and populates
+    /// `missing` with the keys lacking from the manifest.
+    fn find_missing_imports(
+        &'a self,
+        missing: &mut Vec<String>,
+        visited: &mut HashSet<&'a str>,
+        chunk: &'a Chunk,
+    ) {
+        for import in chunk.imports.iter() {
+            if !visited.insert(import) {
+                continue;
+            }
+
+            match self.0.get(import) {
+                Some(chunk) => self.find_missing_imports(missing, visited, chunk),
+                None => missing.push(import.clone()),
+            }
+        }
+    }
+
     /// Returns a list of resources required to include given entrypoint.
-    pub fn resolve_resources(&'a self, entrypoint: &'a str) -> Vec<Resource<'a>> {
-        let Some(chunk) = self.0.get(entrypoint) else {
+    pub(crate) fn resolve_resources(
+        &'a self,
+        entrypoint: &'a str,
+        opts: &ResolveOptions,
+    ) -> Vec<Resource<'a>> {
+        let Some((entrypoint, chunk)) = self.entry_chunk(entrypoint, opts) else {
             return vec![];
         };
 
-        if !chunk.is_entry {
+        if !chunk.is_entry && !opts.allow_non_entry {
             return vec![];
         }
 
         let mut resources: Vec<Resource<'a>> = vec![];
-        self.resolve_imports(&mut resources, entrypoint, chunk);
+        self.resolve_imports(&mut resources, entrypoint, chunk, true);
+
+        if opts.preload_import_css {
+            for resource in resources.iter_mut() {
+                if let Resource::Stylesheet(css) = *resource {
+                    if css != chunk.file && !chunk.css.iter().any(|own| own == css) {
+                        *resource = Resource::PreloadStylesheet(css);
+                    }
+                }
+            }
+        }
+
+        if let Some(css) = opts.single_css.as_deref().and_then(|key| self.0.get(key)) {
+            let stylesheet = Resource::Stylesheet(&css.file);
+            if !entrypoint.ends_with(".css") && !resources.contains(&stylesheet) {
+                resources.push(stylesheet);
+            }
+        }
+
+        if opts.dynamic_import_css {
+            let mut visited = HashSet::new();
+            self.resolve_dynamic_css(&mut resources, &mut visited, entrypoint, false);
+        }
+
+        if !opts.emit_preloads {
+            resources.retain(|resource| !matches!(resource, Resource::PreloadModule(_)));
+        }
+
+        if !opts.preload_assets {
+            resources.retain(|resource| !matches!(resource, Resource::PreloadAsset(_)));
+        }
+
+        // Some plugins emit virtual chunks, e.g. bundling stylesheets only,
+        // whose file is empty and thus must not be referenced.
+        resources.retain(|resource| !resource.uri().is_empty());
+
+        // The entrypoint is preloaded regardless of `emit_preloads`.
+        if opts.entry_as_preload {
+            for resource in resources.iter_mut() {
+                if *resource == Resource::Module(&chunk.file) {
+                    *resource = Resource::PreloadModule(&chunk.file);
+                }
+            }
+        }
 
         // Sorts the resources into following order:
-        // 1. stylesheets
-        // 2. modules
+        // 1. stylesheets, dependencies first
+        // 2. modules and classic scripts
         // 3. preload modules
-        resources.sort();
+        // 4. preload stylesheets
+        // 5. preload assets
+        resources.sort_by(Resource::inclusion_order);
         resources
     }
 
+    /// Returns the sorted keys of all entry chunks matching any of the given
+    /// patterns.
+    #[cfg(feature = "glob")]
+    pub(crate) fn entrypoints_matching(&'a self, patterns: &[glob::Pattern]) -> Vec<&'a str> {
+        let mut entrypoints: Vec<&'a str> = self
+            .0
+            .iter()
+            .filter(|(_, chunk)| chunk.is_entry)
+            .map(|(key, _)| key.as_str())
+            .filter(|key| patterns.iter().any(|pattern| pattern.matches(key)))
+            .collect();
+
+        entrypoints.sort();
+        entrypoints
+    }
+
     /// Recursively iterates through chunks and populates `resources`
     /// with the resources required.
     fn resolve_imports(
@@ -73,39 +681,121 @@ impl<'a> Manifest {
         resources: &mut Vec<Resource<'a>>,
         key: &'a str,
         chunk: &'a Chunk,
+        is_entry: bool,
     ) {
-        for css in chunk.css.iter() {
-            resources.push(Resource::Stylesheet(css));
-        }
-
         for import in chunk.imports.iter() {
             let Some(chunk) = self.0.get(import) else {
                 continue;
             };
 
-            self.resolve_imports(resources, import, chunk);
+            self.resolve_imports(resources, import, chunk, chunk.is_entry);
+        }
+
+        // The stylesheets of imported chunks are pushed first, so that the
+        // stylesheets of the importing chunk take precedence in the cascade.
+        // Some plugins list the same stylesheet more than once.
+        for css in chunk.css.iter() {
+            let stylesheet = Resource::Stylesheet(css);
+            if !resources.contains(&stylesheet) {
+                resources.push(stylesheet);
+            }
+        }
+
+        for asset in chunk.assets.iter() {
+            if preload_destination(asset).is_some() {
+                resources.push(Resource::PreloadAsset(asset));
+            }
         }
 
         // If the chunk is not a entrypoint, it may (optionally) be
         // preloaded.
-        if !chunk.is_entry {
+        if !is_entry {
             resources.push(Resource::PreloadModule(&chunk.file));
             return;
         }
 
-        if key.ends_with(".css") {
-            resources.push(Resource::Stylesheet(&chunk.file));
-        } else if key.ends_with(".js") || key.ends_with(".jsx") || key.ends_with(".ts") || key.ends_with(".tsx") {
-            resources.push(Resource::Module(&chunk.file));
+        if let Some(resource) = entry_resource(key, chunk) {
+            resources.push(resource);
+        }
+    }
+
+    /// Recursively walks the chunk graph and populates `resources` with the
+    /// stylesheets of chunks only reachable through dynamic imports.
+    fn resolve_dynamic_css(
+        &'a self,
+        resources: &mut Vec<Resource<'a>>,
+        visited: &mut HashSet<&'a str>,
+        key: &'a str,
+        dynamic: bool,
+    ) {
+        // Dynamic imports may be circular, hence each chunk is walked once.
+        if !visited.insert(key) {
+            return;
+        }
+
+        let Some(chunk) = self.0.get(key) else {
+            return;
+        };
+
+        // Like `resolve_imports`, dependencies precede the chunk's own
+        // stylesheets to preserve the cascade.
+        for import in chunk.imports.iter() {
+            self.resolve_dynamic_css(resources, visited, import, dynamic);
+        }
+
+        if dynamic {
+            for css in chunk.css.iter() {
+                let stylesheet = Resource::Stylesheet(css);
+                if !resources.contains(&stylesheet) {
+                    resources.push(stylesheet);
+                }
+            }
+        }
+
+        for import in chunk.dynamic_imports.iter() {
+            self.resolve_dynamic_css(resources, visited, import, true);
         }
     }
 }
 
+/// The extensions of entrypoints bundled into modules, unless the chunk is
+/// in a classic format. The chunks of HTML entrypoints, as used by multi-page
+/// apps, refer to the module bundled from the page's scripts.
+const MODULE_EXTENSIONS: &[&str] = &[".js", ".jsx", ".mjs", ".ts", ".tsx", ".mts", ".html"];
+
+/// The extensions of entrypoints bundled into classic scripts.
+const SCRIPT_EXTENSIONS: &[&str] = &[".cjs", ".cts"];
+
+/// Returns the resource including the entry chunk with the given key, or
+/// `None` for unknown extensions.
+fn entry_resource<'a>(key: &str, chunk: &'a Chunk) -> Option<Resource<'a>> {
+    let has_extension = |extensions: &[&str]| extensions.iter().any(|ext| key.ends_with(ext));
+
+    if key.ends_with(".css") {
+        Some(Resource::Stylesheet(&chunk.file))
+    } else if has_extension(SCRIPT_EXTENSIONS) {
+        Some(Resource::Script(&chunk.file))
+    } else if has_extension(MODULE_EXTENSIONS) {
+        if chunk.is_classic_script() {
+            Some(Resource::Script(&chunk.file))
+        } else {
+            Some(Resource::Module(&chunk.file))
+        }
+    } else {
+        None
+    }
+}
+
 #[cfg(test)]
 mod test {
-    use super::{Manifest, Resource};
+    use super::{validate_manifest, Manifest, ResolveOptions, Resource};
 
     const SAMPLE_MANIFEST: &str = include_str!("../test/sample_manifest.json");
+    const SINGLE_CSS_MANIFEST: &str = include_str!("../test/single_css_manifest.json");
+    const DUPLICATE_CSS_MANIFEST: &str = include_str!("../test/duplicate_css_manifest.json");
+    const CASCADE_MANIFEST: &str = include_str!("../test/cascade_manifest.json");
+    const HTML_MANIFEST: &str = include_str!("../test/html_manifest.json");
+    const MJS_MANIFEST: &str = include_str!("../test/mjs_manifest.json");
 
     #[test]
     fn can_deserialize_sample_manifest() {
@@ -118,14 +808,160 @@ mod test {
         let manifest = serde_json::from_str::<Manifest>(SAMPLE_MANIFEST)
             .expect("sample manifest should be deserializable");
 
-        let resources = manifest.resolve_resources("views/foo.js");
+        let resources = manifest.resolve_resources("views/foo.js", &ResolveOptions::default());
         let expected = vec![
-            Resource::Stylesheet("assets/foo-5UjPuW-k.css"),
             Resource::Stylesheet("assets/shared-ChJ_j-JJ.css"),
+            Resource::Stylesheet("assets/foo-5UjPuW-k.css"),
             Resource::Module("assets/foo-BRBmoGS9.js"),
             Resource::PreloadModule("assets/shared-B7PI925R.js"),
         ];
 
         assert_eq!(resources, expected);
     }
+
+    #[test]
+    fn can_resolve_classic_scripts() {
+        let manifest = serde_json::from_str::<Manifest>(include_str!("../test/umd_manifest.json"))
+            .expect("umd manifest should be deserializable");
+
+        assert_eq!(
+            manifest.resolve_resources("lib/widget.js", &ResolveOptions::default()),
+            vec![Resource::Script("assets/widget-Dk3m8PqA.umd.js")]
+        );
+        assert_eq!(
+            manifest.resolve_resources("lib/legacy.js", &ResolveOptions::default()),
+            vec![Resource::Script("assets/legacy-C7vY2nLs.js")]
+        );
+        assert_eq!(
+            manifest.resolve_resources("views/foo.js", &ResolveOptions::default()),
+            vec![Resource::Module("assets/foo-BRBmoGS9.js")]
+        );
+    }
+
+    #[test]
+    fn can_validate_manifest() {
+        let path = concat!(env!("CARGO_MANIFEST_DIR"), "/test/sample_manifest.json");
+        assert_eq!(validate_manifest(path).unwrap(), 2);
+    }
+
+    #[test]
+    fn fails_to_validate_broken_manifest() {
+        let path = concat!(env!("CARGO_MANIFEST_DIR"), "/test/invalid_manifest.json");
+        assert!(matches!(
+            validate_manifest(path),
+            Err(crate::Error::Json(_))
+        ));
+    }
+
+    #[test]
+    fn dedupes_stylesheets_within_chunk() {
+        let manifest = serde_json::from_str::<Manifest>(DUPLICATE_CSS_MANIFEST)
+            .expect("manifest should be deserializable");
+
+        let resources = manifest.resolve_resources("views/app.js", &ResolveOptions::default());
+        let expected = vec![
+            Resource::Stylesheet("assets/app-Lm4xR9Tc.css"),
+            Resource::Module("assets/app-Bk2wN7Qs.js"),
+        ];
+
+        assert_eq!(resources, expected);
+    }
+
+    #[test]
+    fn orders_stylesheets_of_imports_first() {
+        let manifest = serde_json::from_str::<Manifest>(CASCADE_MANIFEST)
+            .expect("manifest should be deserializable");
+
+        let resources = manifest.resolve_resources("views/theme.js", &ResolveOptions::default());
+        let expected = vec![
+            Resource::Stylesheet("assets/z-base-Nf5Qe2Wd.css"),
+            Resource::Stylesheet("assets/a-theme-Bw9Jt6Yc.css"),
+            Resource::Module("assets/theme-Gh7Kp4Vs.js"),
+            Resource::PreloadModule("assets/base-Xc3Lm8Rt.js"),
+        ];
+
+        assert_eq!(resources, expected);
+    }
+
+    #[test]
+    fn can_resolve_html_entrypoint() {
+        let manifest = serde_json::from_str::<Manifest>(HTML_MANIFEST)
+            .expect("manifest should be deserializable");
+
+        let resources = manifest.resolve_resources("index.html", &ResolveOptions::default());
+        let expected = vec![
+            Resource::Stylesheet("assets/shared-Jm8Xc1Ta.css"),
+            Resource::Stylesheet("assets/index-Ub6Gz4Ny.css"),
+            Resource::Module("assets/index-Cw3Lp9Hs.js"),
+            Resource::PreloadModule("assets/shared-Rk5Vn2Qe.js"),
+        ];
+
+        assert_eq!(resources, expected);
+    }
+
+    #[test]
+    fn can_preload_stylesheets_of_imports() {
+        let manifest = serde_json::from_str::<Manifest>(CASCADE_MANIFEST)
+            .expect("manifest should be deserializable");
+
+        let opts = ResolveOptions {
+            preload_import_css: true,
+            ..Default::default()
+        };
+
+        let resources = manifest.resolve_resources("views/theme.js", &opts);
+        let expected = vec![
+            Resource::Stylesheet("assets/a-theme-Bw9Jt6Yc.css"),
+            Resource::Module("assets/theme-Gh7Kp4Vs.js"),
+            Resource::PreloadModule("assets/base-Xc3Lm8Rt.js"),
+            Resource::PreloadStylesheet("assets/z-base-Nf5Qe2Wd.css"),
+        ];
+
+        assert_eq!(resources, expected);
+    }
+
+    #[test]
+    fn can_resolve_mjs_and_cjs_entrypoints() {
+        let manifest = serde_json::from_str::<Manifest>(MJS_MANIFEST)
+            .expect("manifest should be deserializable");
+
+        assert_eq!(
+            manifest.resolve_resources("views/app.mjs", &ResolveOptions::default()),
+            vec![
+                Resource::Stylesheet("assets/app-Ns8Qv3Lc.css"),
+                Resource::Module("assets/app-Wd5Gk2Rn.js"),
+            ]
+        );
+        assert_eq!(
+            manifest.resolve_resources("lib/plugin.cjs", &ResolveOptions::default()),
+            vec![Resource::Script("assets/plugin-Jh4Ty7Bp.js")]
+        );
+    }
+
+    #[test]
+    fn includes_combined_stylesheet_for_every_entry() {
+        let manifest = serde_json::from_str::<Manifest>(SINGLE_CSS_MANIFEST)
+            .expect("manifest should be deserializable");
+
+        let opts = ResolveOptions {
+            single_css: Some("style.css".to_string()),
+            ..Default::default()
+        };
+
+        for (entry, file) in [
+            ("views/app.js", "assets/app-Jw6tB1Hx.js"),
+            ("views/admin.js", "assets/admin-Pq9sD4Mf.js"),
+        ] {
+            let resources = manifest.resolve_resources(entry, &opts);
+            let expected = vec![
+                Resource::Stylesheet("assets/style-Cz8qL3Vn.css"),
+                Resource::Module(file),
+            ];
+
+            assert_eq!(resources, expected);
+        }
+
+        let resources = manifest.resolve_resources("views/app.js", &ResolveOptions::default());
+        assert_eq!(resources, vec![Resource::Module("assets/app-Jw6tB1Hx.js")]);
+    }
 }
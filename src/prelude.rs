@@ -0,0 +1,33 @@
+//! This module re-exports the types commonly required to integrate Vite.
+//!
+//! `Error` is deliberately left out, as it would clash with the error types of
+//! most applications; it remains available as `in_vite::Error`.
+//!
+//! # Examples
+//!
+//! ```
+//! use in_vite::prelude::*;
+//!
+//! let opts = ViteOptions::default()
+//!     .mode(ViteMode::Production)
+//!     .manifest_format(ManifestFormat::Json);
+//!
+//! let vite = Vite::with_options(opts);
+//! let refresh = ViteReactRefresh::new(vite.host(), vite.mode());
+//! let banner = ViteDevBanner::new(vite.host(), vite.mode());
+//! ```
+//!
+
+pub use crate::manifest::ManifestFormat;
+pub use crate::resource::{AssetKind, ResolvedAsset};
+pub use crate::vite::{
+    Vite, ViteBaseTag, ViteCrossOrigin, ViteCssMode, ViteDevBanner, ViteHmrUrl, ViteMode,
+    ViteOptions, VitePrefetch, ViteReactRefresh, ViteReactRefreshOptions, ViteStylesheetOptions,
+    ViteTags,
+};
+
+#[cfg(feature = "leptos")]
+pub use crate::integrations::leptos::ViteAssets;
+
+#[cfg(feature = "sailfish")]
+pub use crate::integrations::sailfish::SailfishTags;
@@ -1,7 +1,13 @@
 //! This module implements the type `Resource`.
 
+use std::cmp::Ordering;
+use std::fmt::{self, Display, Write};
+use std::sync::Arc;
+
+use crate::vite::{is_cross_origin, ViteCrossOrigin, ViteOptions, ViteStylesheetOptions};
+
 /// Enumerates all resources bundled by Vite.
-#[derive(Debug, PartialEq, Eq, PartialOrd, Ord)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
 pub(crate) enum Resource<'a> {
     // Represents a CSS stylesheet to be loaded.
     Stylesheet(&'a str),
@@ -9,24 +15,469 @@ pub(crate) enum Resource<'a> {
     // Represents a JavaScript module to be loaded.
     Module(&'a str),
 
+    // Represents a classic (non-module) script to be loaded, e.g. an UMD or
+    // IIFE build.
+    Script(&'a str),
+
     // Represents a JavaScript module, which can be preloaded
     // using Vite's preload polyfill.
     PreloadModule(&'a str),
+
+    // Represents a CSS stylesheet, which is preloaded instead of applied
+    // right away.
+    PreloadStylesheet(&'a str),
+
+    // Represents an asset, e.g. a font or data file, which can be preloaded.
+    PreloadAsset(&'a str),
+}
+
+/// Enumerates the kinds of assets `Vite::resolve` resolves to.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum AssetKind {
+    /// A CSS stylesheet, included using `<link rel="stylesheet">`.
+    Stylesheet,
+
+    /// A JavaScript module, included using `<script type="module">`.
+    Module,
+
+    /// A classic script, included using `<script>`.
+    Script,
+
+    /// A JavaScript module, preloaded using `<link rel="modulepreload">`.
+    PreloadModule,
+
+    /// A CSS stylesheet of an imported chunk, preloaded using
+    /// `<link rel="preload" as="style">`.
+    PreloadStylesheet,
+
+    /// An asset, e.g. a font or data file, preloaded using
+    /// `<link rel="preload">`.
+    PreloadAsset,
+}
+
+/// Represents an asset resolved for inclusion along with its URL.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ResolvedAsset {
+    /// The kind of the asset, determining how it is included.
+    pub kind: AssetKind,
+
+    /// The URL of the asset, i.e. its path in the manifest prefixed with the
+    /// base or the host of the development server.
+    pub url: String,
+}
+
+/// Transforms the URLs of resources before they are rendered.
+#[derive(Clone)]
+pub(crate) struct UrlTransform(pub Arc<dyn Fn(&str) -> String + Send + Sync>);
+
+impl fmt::Debug for UrlTransform {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("UrlTransform")
+    }
+}
+
+/// Options controlling how resources are rendered into tags, e.g. by
+/// `render_tags`.
+///
+/// The defaults match those of `ViteOptions`; options configured otherwise
+/// are carried over using `TagOptions::from(&opts)`.
+#[derive(Debug, Clone)]
+#[non_exhaustive]
+pub struct TagOptions {
+    /// The base URIs are prefixed with.
+    pub base: String,
+
+    /// Whether classic scripts are deferred.
+    pub defer_classic: bool,
+
+    /// Whether `<link>` tags are self-closing.
+    pub xhtml: bool,
+
+    /// How the `crossorigin` attribute is applied to every tag. Without a
+    /// page, `ViteCrossOrigin::Auto` considers every absolute base
+    /// cross-origin.
+    pub crossorigin: ViteCrossOrigin,
+
+    /// Whether stylesheets and scripts are tracked by Turbo.
+    pub turbo_track: bool,
+
+    /// Whether stylesheets are loaded asynchronously.
+    pub async_css: bool,
+
+    /// The `referrerpolicy` attribute applied to every tag, if any.
+    pub referrer_policy: Option<String>,
+
+    /// The separator written between tags.
+    pub line_separator: String,
+
+    /// The function transforming every URL after the base is prefixed, if
+    /// any.
+    pub(crate) url_transform: Option<UrlTransform>,
+}
+
+impl TagOptions {
+    /// Returns the `crossorigin` attribute, if any, including its leading
+    /// space.
+    pub(crate) fn crossorigin_attr(&self) -> &'static str {
+        match self.crossorigin {
+            ViteCrossOrigin::Disabled => "",
+            ViteCrossOrigin::Anonymous => " crossorigin",
+            ViteCrossOrigin::UseCredentials => r#" crossorigin="use-credentials""#,
+            ViteCrossOrigin::Auto if is_cross_origin(&self.base, None) => " crossorigin",
+            ViteCrossOrigin::Auto => "",
+        }
+    }
+
+    /// Returns the `crossorigin` and `referrerpolicy` attributes, if any.
+    pub(crate) fn cors(&self) -> Cors<'_> {
+        Cors {
+            crossorigin: self.crossorigin_attr(),
+            referrer_policy: self.referrer_policy.as_deref(),
+        }
+    }
+
+    /// Returns the URL of `uri` prefixed with the base and transformed by
+    /// `url_transform`, if any.
+    pub(crate) fn url<'u>(&'u self, uri: &'u str) -> TagUrl<'u> {
+        TagUrl { opts: self, uri }
+    }
+}
+
+impl Default for TagOptions {
+    fn default() -> Self {
+        Self {
+            base: "/".to_string(),
+            defer_classic: true,
+            xhtml: true,
+            crossorigin: ViteCrossOrigin::Disabled,
+            turbo_track: false,
+            async_css: false,
+            referrer_policy: None,
+            line_separator: "\n".to_string(),
+            url_transform: None,
+        }
+    }
+}
+
+impl From<&ViteOptions> for TagOptions {
+    fn from(opts: &ViteOptions) -> Self {
+        Self {
+            base: opts.base.clone(),
+            defer_classic: opts.defer_classic,
+            xhtml: opts.xhtml,
+            crossorigin: opts.crossorigin.clone(),
+            turbo_track: opts.turbo_track,
+            async_css: opts.async_css,
+            referrer_policy: opts.referrer_policy.clone(),
+            line_separator: opts.line_separator.clone(),
+            url_transform: opts.url_transform.clone(),
+        }
+    }
+}
+
+/// Displays the `crossorigin` and `referrerpolicy` attributes of a tag, each
+/// including its leading space, without allocating.
+pub(crate) struct Cors<'a> {
+    crossorigin: &'a str,
+    referrer_policy: Option<&'a str>,
+}
+
+impl Display for Cors<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.crossorigin)?;
+        if let Some(policy) = self.referrer_policy {
+            write!(f, r#" referrerpolicy="{policy}""#)?;
+        }
+
+        Ok(())
+    }
+}
+
+/// Displays the `data-vite-entry` attribute of a tag, if any, including its
+/// leading space.
+struct EntryAttr<'a>(Option<&'a str>);
+
+impl Display for EntryAttr<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.0 {
+            Some(entry) => write!(f, r#" data-vite-entry="{entry}""#),
+            None => Ok(()),
+        }
+    }
+}
+
+/// Displays the `title` attribute of a stylesheet, if any, including its
+/// leading space, escaping its value.
+struct Title<'a>(Option<&'a str>);
+
+impl Display for Title<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let Some(title) = self.0 else {
+            return Ok(());
+        };
+
+        write!(f, r#" title="{}""#, Escaped(title))
+    }
+}
+
+/// Displays the value of an attribute, escaping characters which would end
+/// the attribute or the tag early.
+pub(crate) struct Escaped<'a>(pub(crate) &'a str);
+
+impl Display for Escaped<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for c in self.0.chars() {
+            match c {
+                '&' => f.write_str("&amp;")?,
+                '"' => f.write_str("&quot;")?,
+                '<' => f.write_str("&lt;")?,
+                '>' => f.write_str("&gt;")?,
+                c => f.write_char(c)?,
+            }
+        }
+
+        Ok(())
+    }
+}
+
+/// Displays the URL of a resource as rendered into tags, i.e. prefixed with
+/// the base and transformed by `url_transform`, if any.
+pub(crate) struct TagUrl<'a> {
+    opts: &'a TagOptions,
+    uri: &'a str,
+}
+
+impl Display for TagUrl<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let url = Url::new(&self.opts.base, self.uri);
+        match &self.opts.url_transform {
+            Some(transform) => f.write_str(&(transform.0)(&url.to_string())),
+            None => url.fmt(f),
+        }
+    }
+}
+
+/// Renders the tags including the given resources in order, each given by its
+/// kind and URI, which is prefixed with the base of `opts`. Exposes the
+/// formatting `Vite::to_html` uses independent of any manifest.
+///
+/// # Examples
+///
+/// ```
+/// use in_vite::{render_tags, AssetKind, TagOptions};
+///
+/// let html = render_tags(&[(AssetKind::Module, "assets/app.js")], &TagOptions::default());
+/// assert_eq!(html, r#"<script type="module" src="/assets/app.js"></script>"#);
+/// ```
+pub fn render_tags(resources: &[(AssetKind, &str)], opts: &TagOptions) -> String {
+    let mut html = String::new();
+    for (i, (kind, uri)) in resources.iter().enumerate() {
+        if i > 0 {
+            html.push_str(&opts.line_separator);
+        }
+
+        // Writing into a string cannot fail.
+        let _ = Resource::new(*kind, uri).write_html(&mut html, opts);
+    }
+
+    html
 }
 
 impl<'a> Resource<'a> {
+    /// Constructs the resource of the given kind.
+    pub fn new(kind: AssetKind, uri: &'a str) -> Self {
+        match kind {
+            AssetKind::Stylesheet => Self::Stylesheet(uri),
+            AssetKind::Module => Self::Module(uri),
+            AssetKind::Script => Self::Script(uri),
+            AssetKind::PreloadModule => Self::PreloadModule(uri),
+            AssetKind::PreloadStylesheet => Self::PreloadStylesheet(uri),
+            AssetKind::PreloadAsset => Self::PreloadAsset(uri),
+        }
+    }
+
+    /// Writes the appropriate HTML code required to include the resource into
+    /// `w`.
+    pub fn write_html<W: Write>(&self, w: &mut W, opts: &TagOptions) -> fmt::Result {
+        self.write_html_with_entry(w, opts, None, None)
+    }
+
+    /// Writes the appropriate HTML code required to include the resource into
+    /// `w`, annotating it with the entrypoint it originates from, if any.
+    /// Stylesheets are linked with the `rel` and `title` of `stylesheet`, if
+    /// given, e.g. for alternate stylesheets.
+    pub fn write_html_with_entry<W: Write>(
+        &self,
+        w: &mut W,
+        opts: &TagOptions,
+        entry: Option<&str>,
+        stylesheet: Option<&ViteStylesheetOptions>,
+    ) -> fmt::Result {
+        let url = opts.url(self.uri());
+        let end = if opts.xhtml { " />" } else { ">" };
+        let cors = opts.cors();
+        let track = if opts.turbo_track {
+            r#" data-turbo-track="reload""#
+        } else {
+            ""
+        };
+        let attrs = EntryAttr(entry);
+
+        let rel = stylesheet
+            .and_then(|stylesheet| stylesheet.rel.as_deref())
+            .unwrap_or("stylesheet");
+        let title = Title(stylesheet.and_then(|stylesheet| stylesheet.title.as_deref()));
 
-    /// Converts the resource into the appropriate HTML code required to include
-    /// the resource.
-    pub fn to_html(&'a self) -> String {
         match *self {
-            Self::Stylesheet(uri) => format!(r#"<link rel="stylesheet" href="/{uri}" />"#),
-            Self::Module(uri) => format!(r#"<script type="module" src="/{uri}"></script>"#),
-            Self::PreloadModule(uri) => {
-                format!(r#"<link rel="modulepreload" href="/{uri}" />"#)
+            Self::Stylesheet(_) if opts.async_css => {
+                write!(
+                    w,
+                    r#"<link rel="preload" as="style"{title}{cors} href="{url}" onload="this.onload=null;this.rel='{rel}'"{track}{attrs}{end}"#
+                )?;
+                write!(
+                    w,
+                    r#"<noscript><link rel="{rel}"{title}{cors} href="{url}"{track}{attrs}{end}</noscript>"#
+                )
+            }
+            Self::Stylesheet(_) => {
+                write!(
+                    w,
+                    r#"<link rel="{rel}"{title}{cors} href="{url}"{track}{attrs}{end}"#
+                )
+            }
+            Self::Module(_) => {
+                write!(
+                    w,
+                    r#"<script type="module"{cors} src="{url}"{track}{attrs}></script>"#
+                )
+            }
+            Self::Script(_) if opts.defer_classic => {
+                write!(
+                    w,
+                    r#"<script defer{cors} src="{url}"{track}{attrs}></script>"#
+                )
             }
+            Self::Script(_) => write!(w, r#"<script{cors} src="{url}"{track}{attrs}></script>"#),
+            Self::PreloadModule(_) => {
+                write!(
+                    w,
+                    r#"<link rel="modulepreload"{cors} href="{url}"{attrs}{end}"#
+                )
+            }
+            Self::PreloadStylesheet(_) => {
+                write!(
+                    w,
+                    r#"<link rel="preload" as="style"{cors} href="{url}"{attrs}{end}"#
+                )
+            }
+            Self::PreloadAsset(uri) => {
+                let (destination, crossorigin) =
+                    preload_destination(uri).unwrap_or(("fetch", true));
+
+                // Fonts and fetches must be requested in CORS mode regardless.
+                let cors = match opts.crossorigin_attr() {
+                    "" if crossorigin => Cors {
+                        crossorigin: " crossorigin",
+                        ..cors
+                    },
+                    _ => cors,
+                };
+                write!(
+                    w,
+                    r#"<link rel="preload" as="{destination}"{cors} href="{url}"{attrs}{end}"#
+                )
+            }
+        }
+    }
+
+    /// Writes a `<link rel="prefetch">` hinting the resource into `w`, e.g. for
+    /// a probable next route.
+    pub fn write_prefetch<W: Write>(&self, w: &mut W, opts: &TagOptions) -> fmt::Result {
+        let url = opts.url(self.uri());
+        let end = if opts.xhtml { " />" } else { ">" };
+        let cors = opts.cors();
+
+        write!(w, r#"<link rel="prefetch"{cors} href="{url}"{end}"#)
+    }
+
+    /// Orders resources for inclusion, i.e. by their kind in the order of the
+    /// variants. Stylesheets retain the order they've been resolved in, as it
+    /// determines the cascade, whereas other resources are ordered by URI.
+    ///
+    /// Intended for stable sorts.
+    pub fn inclusion_order(&self, other: &Self) -> Ordering {
+        match (self, other) {
+            (Self::Stylesheet(_), Self::Stylesheet(_)) => Ordering::Equal,
+            _ => self.cmp(other),
+        }
+    }
+
+    /// Returns the URI of the resource.
+    pub fn uri(&self) -> &'a str {
+        match *self {
+            Self::Stylesheet(uri)
+            | Self::Module(uri)
+            | Self::Script(uri)
+            | Self::PreloadModule(uri)
+            | Self::PreloadStylesheet(uri)
+            | Self::PreloadAsset(uri) => uri,
+        }
+    }
+
+    /// Returns the kind of the resource.
+    pub fn kind(self) -> AssetKind {
+        match self {
+            Self::Stylesheet(_) => AssetKind::Stylesheet,
+            Self::Module(_) => AssetKind::Module,
+            Self::Script(_) => AssetKind::Script,
+            Self::PreloadModule(_) => AssetKind::PreloadModule,
+            Self::PreloadStylesheet(_) => AssetKind::PreloadStylesheet,
+            Self::PreloadAsset(_) => AssetKind::PreloadAsset,
         }
     }
+}
+
+/// Guesses the destination, i.e. the `as` attribute, of a preloaded asset from
+/// its extension along with whether it must be fetched in CORS mode.
+///
+/// Returns `None` for assets which cannot be preloaded meaningfully.
+pub(crate) fn preload_destination(uri: &str) -> Option<(&'static str, bool)> {
+    let (_, extension) = uri.rsplit_once('.')?;
+    match extension.to_ascii_lowercase().as_str() {
+        "woff2" | "woff" | "ttf" | "otf" => Some(("font", true)),
+        "png" | "jpg" | "jpeg" | "gif" | "svg" | "webp" | "avif" => Some(("image", false)),
+        "json" | "wasm" => Some(("fetch", true)),
+        _ => None,
+    }
+}
 
+/// Displays the URL of a resource without allocating, i.e. its URI prefixed
+/// with a base.
+///
+/// Backslashes, as emitted by some plugins on Windows, are normalized to
+/// forward slashes.
+pub(crate) struct Url<'a> {
+    base: &'a str,
+    uri: &'a str,
 }
 
+impl<'a> Url<'a> {
+    pub fn new(base: &'a str, uri: &'a str) -> Self {
+        Self { base, uri }
+    }
+}
+
+impl Display for Url<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.base)?;
+        for (i, segment) in self.uri.split('\\').enumerate() {
+            if i > 0 {
+                f.write_char('/')?;
+            }
+
+            f.write_str(segment)?;
+        }
+
+        Ok(())
+    }
+}
@@ -1,8 +1,45 @@
 //! This module implements the type `Vite` and `ViteOptions`.
 
+use std::borrow::Cow;
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::fmt::Write;
+use std::io::Read;
+use std::sync::{Arc, OnceLock, PoisonError, RwLock};
+
 use crate::error::Error;
-use crate::manifest::Manifest;
-use crate::resource::Resource;
+use crate::manifest::{
+    ChunkMetadata, Manifest, ManifestDiff, ManifestFormat, ManifestReader, ResolutionReport,
+    ResolveOptions, SsrManifest,
+};
+use crate::resource::{
+    preload_destination, AssetKind, Escaped, ResolvedAsset, Resource, TagOptions, UrlTransform,
+};
+
+/// The preamble `ViteReactRefresh` runs after importing the refresh runtime,
+/// as emitted by `@vitejs/plugin-react`. It may serve as a starting point for
+/// a customized preamble, see `ViteReactRefresh::preamble`.
+pub const REACT_REFRESH_PREAMBLE: &str = r#"RefreshRuntime.injectIntoGlobalHook(window)
+window.$RefreshReg$ = () => {}
+window.$RefreshSig$ = () => (type) => type
+window.__vite_plugin_react_preamble_installed__ = true"#;
+
+/// The key of the chunk `@vitejs/plugin-legacy` bundles SystemJS and the
+/// polyfills required by legacy browsers into.
+const LEGACY_POLYFILLS: &str = "vite/legacy-polyfills-legacy";
+
+/// Fetches the targets of `<link rel="modulepreload">` in browsers lacking
+/// support, akin to Vite's `vite/modulepreload-polyfill`.
+const MODULEPRELOAD_POLYFILL: &str = concat!(
+    r#"(function(){const r=document.createElement("link").relList;"#,
+    r#"if(r&&r.supports&&r.supports("modulepreload"))return;"#,
+    r#"for(const l of document.querySelectorAll('link[rel="modulepreload"]'))p(l);"#,
+    r#"new MutationObserver(m=>{for(const e of m)if(e.type==="childList")"#,
+    r#"for(const n of e.addedNodes)if(n.tagName==="LINK"&&n.rel==="modulepreload")p(n)})"#,
+    r#".observe(document,{childList:true,subtree:true});"#,
+    r#"function p(l){if(l.ep)return;l.ep=true;fetch(l.href,{integrity:l.integrity,"#,
+    r#"referrerPolicy:l.referrerPolicy,credentials:l.crossOrigin==="use-credentials"?"include":"#,
+    r#"l.crossOrigin==="anonymous"?"omit":"same-origin"})}})();"#,
+);
 
 #[derive(Default, Debug, Clone, PartialEq, Eq)]
 pub enum ViteMode {
@@ -11,11 +48,116 @@ pub enum ViteMode {
     Production,
 }
 
+/// Parses the mode from `development` or `production`, e.g. as passed to the
+/// template integrations.
+impl std::str::FromStr for ViteMode {
+    type Err = Error;
+
+    fn from_str(mode: &str) -> Result<Self, Self::Err> {
+        match mode {
+            "development" => Ok(ViteMode::Development),
+            "production" => Ok(ViteMode::Production),
+            _ => Err(Error::InvalidMode(mode.to_string())),
+        }
+    }
+}
+
+/// Enumerates how the `crossorigin` attribute is applied to tags rendered in
+/// production.
+#[derive(Default, Debug, Clone, PartialEq, Eq)]
+pub enum ViteCrossOrigin {
+    /// The attribute is omitted.
+    #[default]
+    Disabled,
+
+    /// The attribute is set to `anonymous`, i.e. rendered as `crossorigin`.
+    Anonymous,
+
+    /// The attribute is set to `use-credentials`.
+    UseCredentials,
+
+    /// The attribute is set to `anonymous`, if the resources are served from
+    /// an origin other than that of the page, e.g. a CDN. The page's origin
+    /// is the host passed to `Vite::to_html_with_host`; without one, every
+    /// absolute URL is considered cross-origin.
+    Auto,
+}
+
+/// Enumerates how stylesheets are included in production.
+#[derive(Default, Debug, Clone, PartialEq, Eq)]
+pub enum ViteCssMode {
+    /// Each stylesheet is linked using `<link rel="stylesheet">`.
+    #[default]
+    Link,
+
+    /// All stylesheets are imported by a single `<style>` using `@import`
+    /// rules, e.g. for embeddings into a shadow DOM.
+    Import,
+
+    /// Each stylesheet is inlined as `<style>`, read from `out_dir`.
+    Inline,
+}
+
 pub struct ViteOptions {
     pub(crate) host: String,
     pub(crate) manifest_source: Option<String>,
     pub(crate) manifest_path: String,
     pub(crate) mode: ViteMode,
+    pub(crate) dev_root: Option<String>,
+    pub(crate) base: String,
+    pub(crate) manifest_format: ManifestFormat,
+    pub(crate) dynamic_import_css: bool,
+    pub(crate) allow_non_entry: bool,
+    pub(crate) defer_classic: bool,
+    pub(crate) manifest_pointer: Option<String>,
+    pub(crate) combine_entries: bool,
+    pub(crate) emit_preloads: bool,
+    pub(crate) fallback_entry: Option<String>,
+    pub(crate) preload_assets: bool,
+    pub(crate) xhtml: bool,
+    pub(crate) tag_entry_attribute: bool,
+    pub(crate) dev_extension: Option<String>,
+    pub(crate) out_dir: String,
+    pub(crate) inline_stylesheets: Vec<String>,
+    pub(crate) client_without_entries: bool,
+    pub(crate) crossorigin: ViteCrossOrigin,
+    pub(crate) ssr_manifest_source: Option<String>,
+    pub(crate) ssr_manifest_path: String,
+    pub(crate) turbo_track: bool,
+    pub(crate) manifest_reader: Option<ManifestReader>,
+    pub(crate) modulepreload_polyfill: bool,
+    pub(crate) client_attributes: Vec<(String, String)>,
+    pub(crate) cache_rendered: bool,
+    pub(crate) single_css: Option<String>,
+    pub(crate) fallback_to_dev: bool,
+    pub(crate) exclude: Vec<String>,
+    pub(crate) async_css: bool,
+    pub(crate) additional_manifests: Vec<String>,
+    pub(crate) css_mode: ViteCssMode,
+    pub(crate) mark_safe: bool,
+    pub(crate) line_separator: String,
+    pub(crate) preload_import_css: bool,
+    pub(crate) entry_as_preload: bool,
+    pub(crate) legacy: bool,
+    pub(crate) shared_origin: Option<String>,
+    pub(crate) shared_prefix: String,
+    pub(crate) case_insensitive_lookup: bool,
+    pub(crate) dev_check_timeout: std::time::Duration,
+    pub(crate) dev_check_retries: u32,
+    pub(crate) debug_comments: bool,
+    pub(crate) per_entry_dev_fallback: bool,
+
+    // Whether `manifest_path` was set explicitly, disabling the fallback to
+    // the location of Vite 4.
+    pub(crate) manifest_path_explicit: bool,
+    pub(crate) stylesheet_overrides: BTreeMap<String, ViteStylesheetOptions>,
+    pub(crate) production_envs: Vec<String>,
+    pub(crate) referrer_policy: Option<String>,
+    pub(crate) url_transform: Option<UrlTransform>,
+    pub(crate) environment: Option<String>,
+    // Whether `mode` was guessed from the environment rather than set, which
+    // `no_guess` resets.
+    pub(crate) mode_guessed: bool,
 }
 
 impl Default for ViteOptions {
@@ -25,6 +167,56 @@ impl Default for ViteOptions {
             manifest_source: None,
             manifest_path: "dist/.vite/manifest.json".to_string(),
             mode: ViteMode::default(),
+            dev_root: None,
+            base: "/".to_string(),
+            manifest_format: ManifestFormat::default(),
+            dynamic_import_css: false,
+            allow_non_entry: false,
+            defer_classic: true,
+            manifest_pointer: None,
+            combine_entries: false,
+            emit_preloads: true,
+            fallback_entry: None,
+            preload_assets: false,
+            xhtml: true,
+            tag_entry_attribute: false,
+            dev_extension: None,
+            out_dir: "dist".to_string(),
+            inline_stylesheets: Vec::new(),
+            client_without_entries: true,
+            crossorigin: ViteCrossOrigin::Disabled,
+            ssr_manifest_source: None,
+            ssr_manifest_path: "dist/.vite/ssr-manifest.json".to_string(),
+            turbo_track: false,
+            manifest_reader: None,
+            modulepreload_polyfill: false,
+            client_attributes: Vec::new(),
+            cache_rendered: false,
+            single_css: None,
+            fallback_to_dev: false,
+            exclude: Vec::new(),
+            async_css: false,
+            additional_manifests: Vec::new(),
+            css_mode: ViteCssMode::Link,
+            mark_safe: true,
+            line_separator: "\n".to_string(),
+            preload_import_css: false,
+            entry_as_preload: false,
+            legacy: false,
+            shared_origin: None,
+            shared_prefix: "shared/".to_string(),
+            case_insensitive_lookup: false,
+            dev_check_timeout: std::time::Duration::from_millis(500),
+            dev_check_retries: 3,
+            debug_comments: false,
+            per_entry_dev_fallback: false,
+            manifest_path_explicit: false,
+            stylesheet_overrides: BTreeMap::new(),
+            production_envs: vec!["production".to_string()],
+            referrer_policy: None,
+            url_transform: None,
+            environment: None,
+            mode_guessed: false,
         }
         .guess_mode()
     }
@@ -37,6 +229,56 @@ impl ViteOptions {
             manifest_source: None,
             manifest_path: "dist/.vite/manifest.json".to_string(),
             mode: ViteMode::default(),
+            dev_root: None,
+            base: "/".to_string(),
+            manifest_format: ManifestFormat::default(),
+            dynamic_import_css: false,
+            allow_non_entry: false,
+            defer_classic: true,
+            manifest_pointer: None,
+            combine_entries: false,
+            emit_preloads: true,
+            fallback_entry: None,
+            preload_assets: false,
+            xhtml: true,
+            tag_entry_attribute: false,
+            dev_extension: None,
+            out_dir: "dist".to_string(),
+            inline_stylesheets: Vec::new(),
+            client_without_entries: true,
+            crossorigin: ViteCrossOrigin::Disabled,
+            ssr_manifest_source: None,
+            ssr_manifest_path: "dist/.vite/ssr-manifest.json".to_string(),
+            turbo_track: false,
+            manifest_reader: None,
+            modulepreload_polyfill: false,
+            client_attributes: Vec::new(),
+            cache_rendered: false,
+            single_css: None,
+            fallback_to_dev: false,
+            exclude: Vec::new(),
+            async_css: false,
+            additional_manifests: Vec::new(),
+            css_mode: ViteCssMode::Link,
+            mark_safe: true,
+            line_separator: "\n".to_string(),
+            preload_import_css: false,
+            entry_as_preload: false,
+            legacy: false,
+            shared_origin: None,
+            shared_prefix: "shared/".to_string(),
+            case_insensitive_lookup: false,
+            dev_check_timeout: std::time::Duration::from_millis(500),
+            dev_check_retries: 3,
+            debug_comments: false,
+            per_entry_dev_fallback: false,
+            manifest_path_explicit: false,
+            stylesheet_overrides: BTreeMap::new(),
+            production_envs: vec!["production".to_string()],
+            referrer_policy: None,
+            url_transform: None,
+            environment: None,
+            mode_guessed: false,
         }
     }
 
@@ -46,21 +288,463 @@ impl ViteOptions {
         self
     }
 
+    /// Sets the host from the given environment variable, e.g.
+    /// `VITE_DEV_SERVER_URL`, if it is set. Otherwise the host is left as is.
+    pub fn host_from_env(mut self, var: &str) -> Self {
+        if let Ok(host) = std::env::var(var) {
+            self.host = host.trim_end_matches('/').to_string();
+        }
+
+        self
+    }
+
+    /// Sets the public base path (or URL) assets are served from, analogous
+    /// to Vite's `base` option. Defaults to `/`.
+    pub fn base(mut self, base: &str) -> Self {
+        self.base = normalize_base(base);
+        self
+    }
+
     /// Sets the manifest source to deserialize the manifest from.
     pub fn source<S: Into<String>>(mut self, source: Option<S>) -> Self {
-        self.manifest_source = source.and_then(|src| Some(src.into()));
+        self.manifest_source = source.map(|src| src.into());
         self
     }
 
     /// Sets the path from where to load and deserialize the manifest from.
+    ///
+    /// If left at its default, `dist/.vite/manifest.json` as written by Vite 5,
+    /// the location of Vite 4, `dist/manifest.json`, is tried as well.
     pub fn manifest_path<S: Into<String>>(mut self, path: S) -> Self {
         self.manifest_path = path.into();
+        self.manifest_path_explicit = true;
+        self
+    }
+
+    /// Sets the path from where to load the manifest from, relative to the
+    /// directory of the current executable instead of the working directory,
+    /// e.g. for binaries distributed along with their assets.
+    ///
+    /// Should the location of the executable be unavailable, `path` is used
+    /// as is, i.e. relative to the working directory.
+    pub fn manifest_path_relative_to_exe(mut self, path: &str) -> Self {
+        self.manifest_path = exe_relative_path(std::env::current_exe(), path);
+        self.manifest_path_explicit = true;
+        self
+    }
+
+    /// Sets the format the manifest is deserialized from.
+    pub fn manifest_format(mut self, format: ManifestFormat) -> Self {
+        self.manifest_format = format;
+        self
+    }
+
+    /// Sets whether stylesheets of dynamically imported chunks should be
+    /// included as well, e.g. to avoid a flash of unstyled content for a
+    /// code-split route known to be rendered.
+    ///
+    /// Only stylesheets are included; dynamically imported scripts are left
+    /// to be loaded on demand.
+    pub fn dynamic_import_css(mut self, enabled: bool) -> Self {
+        self.dynamic_import_css = enabled;
+        self
+    }
+
+    /// Sets whether chunks Vite did not flag as entry, e.g. a shared worker,
+    /// may be requested as entrypoints and are resolved as if they were.
+    ///
+    /// By default, requesting such a chunk resolves no resources.
+    pub fn allow_non_entry(mut self, allowed: bool) -> Self {
+        self.allow_non_entry = allowed;
+        self
+    }
+
+    /// Sets whether classic (non-module) scripts are deferred, matching the
+    /// semantics of module scripts. Enabled by default.
+    pub fn defer_classic(mut self, defer: bool) -> Self {
+        self.defer_classic = defer;
+        self
+    }
+
+    /// Sets a JSON pointer, e.g. `/manifest`, locating the manifest within a
+    /// larger document, for pipelines wrapping Vite's manifest.
+    pub fn manifest_pointer(mut self, pointer: Option<&str>) -> Self {
+        self.manifest_pointer = pointer.map(|pointer| pointer.to_string());
+        self
+    }
+
+    /// Sets whether development entrypoints, including `@vite/client`, are
+    /// imported from a single inline module script instead of one script per
+    /// entrypoint. Production output is unaffected.
+    pub fn combine_entries(mut self, combine: bool) -> Self {
+        self.combine_entries = combine;
+        self
+    }
+
+    /// Sets whether imported chunks are preloaded using `modulepreload` links.
+    /// Enabled by default.
+    pub fn emit_preloads(mut self, emit: bool) -> Self {
+        self.emit_preloads = emit;
+        self
+    }
+
+    /// Sets an entrypoint whose resources are included in place of requested
+    /// entrypoints, which resolve to no resources in production, e.g.
+    /// because they're missing from the current build.
+    pub fn fallback_entry<S: Into<String>>(mut self, entry: Option<S>) -> Self {
+        self.fallback_entry = entry.map(Into::into);
+        self
+    }
+
+    /// Sets whether assets referenced by the chunks, e.g. fonts, images or
+    /// data files, are preloaded using `<link rel="preload">`. Only assets
+    /// whose destination can be guessed from their extension are preloaded.
+    /// Disabled by default.
+    pub fn preload_assets(mut self, preload: bool) -> Self {
+        self.preload_assets = preload;
+        self
+    }
+
+    /// Sets whether `<link>` tags are self-closing, i.e. end with ` />` as
+    /// required by XHTML, instead of `>` as HTML5 void elements. Enabled by
+    /// default.
+    pub fn xhtml(mut self, xhtml: bool) -> Self {
+        self.xhtml = xhtml;
+        self
+    }
+
+    /// Sets whether tags rendered in production carry the entrypoint they
+    /// originate from as `data-vite-entry` attribute. Resources shared by
+    /// several entrypoints carry the first one requiring them. Disabled by
+    /// default.
+    pub fn tag_entry_attribute(mut self, enabled: bool) -> Self {
+        self.tag_entry_attribute = enabled;
+        self
+    }
+
+    /// Sets an extension, e.g. `".ts"`, appended to extensionless entrypoints
+    /// in development, as they're resolved by the dev server by their actual
+    /// file name.
+    pub fn dev_extension(mut self, extension: Option<&str>) -> Self {
+        self.dev_extension = extension.map(str::to_string);
+        self
+    }
+
+    /// Sets the directory Vite emits the built files into, i.e.
+    /// `build.outDir`. Defaults to `"dist"`.
+    pub fn out_dir<S: Into<String>>(mut self, dir: S) -> Self {
+        self.out_dir = dir.into();
+        self
+    }
+
+    /// Sets the stylesheets, named by their file in the manifest or a suffix
+    /// thereof, which are inlined as `<style>` in production instead of being
+    /// linked, e.g. to inline critical CSS. The stylesheets are read from
+    /// `out_dir`.
+    ///
+    /// Use `css_mode` with `ViteCssMode::Inline` to inline every stylesheet.
+    pub fn inline_stylesheets(mut self, stylesheets: Vec<String>) -> Self {
+        self.inline_stylesheets = stylesheets;
+        self
+    }
+
+    /// Sets whether Vite's client is included in development, even if no
+    /// entrypoint is requested, e.g. to hot reload pages lacking scripts.
+    /// Enabled by default.
+    pub fn client_without_entries(mut self, include: bool) -> Self {
+        self.client_without_entries = include;
+        self
+    }
+
+    /// Sets how the `crossorigin` attribute is applied to tags rendered in
+    /// production. Disabled by default.
+    pub fn crossorigin(mut self, crossorigin: ViteCrossOrigin) -> Self {
+        self.crossorigin = crossorigin;
+        self
+    }
+
+    /// Sets the source of the SSR manifest, used instead of reading the file
+    /// at `ssr_manifest_path`.
+    pub fn ssr_manifest_source<S: Into<String>>(mut self, source: Option<S>) -> Self {
+        self.ssr_manifest_source = source.map(Into::into);
+        self
+    }
+
+    /// Sets the path of the SSR manifest emitted by `vite build --ssrManifest`.
+    /// Defaults to `"dist/.vite/ssr-manifest.json"`.
+    pub fn ssr_manifest_path<S: Into<String>>(mut self, path: S) -> Self {
+        self.ssr_manifest_path = path.into();
+        self
+    }
+
+    /// Sets whether entry modules and stylesheets rendered in production carry
+    /// `data-turbo-track="reload"`, so that Turbo reloads the page once they
+    /// change. Preloads are not tracked. Disabled by default.
+    pub fn turbo_track(mut self, track: bool) -> Self {
+        self.turbo_track = track;
+        self
+    }
+
+    /// Sets a function providing a fresh reader over the manifest whenever it
+    /// is loaded, e.g. to read it from a decompression stream or an archive.
+    /// It is used instead of `manifest_path`, unless a `source` is set.
+    pub fn manifest_reader(
+        mut self,
+        reader: Box<dyn Fn() -> Result<Box<dyn Read>, Error> + Send + Sync>,
+    ) -> Self {
+        self.manifest_reader = Some(ManifestReader(reader.into()));
+        self
+    }
+
+    /// Sets whether an inline polyfill for `<link rel="modulepreload">` is
+    /// prepended to the tags rendered in production, for browsers lacking
+    /// support. Disabled by default.
+    pub fn modulepreload_polyfill(mut self, polyfill: bool) -> Self {
+        self.modulepreload_polyfill = polyfill;
+        self
+    }
+
+    /// Sets additional attributes, e.g. `("data-vite-client", "")`, rendered
+    /// on the script including `@vite/client` in development. Values are
+    /// escaped, whereas attributes with invalid names are skipped.
+    pub fn client_attributes(mut self, attributes: Vec<(String, String)>) -> Self {
+        self.client_attributes = attributes
+            .into_iter()
+            .filter(|(name, _)| {
+                let valid = is_attribute_name(name);
+                if !valid {
+                    tracing::warn!("skipping client attribute with invalid name '{name}'");
+                }
+
+                valid
+            })
+            .collect();
+        self
+    }
+
+    /// Sets whether the HTML rendered in production is cached per host and
+    /// ordered list of entrypoints until the manifest is invalidated, turning repeated renders
+    /// into a lookup. Disabled by default.
+    ///
+    /// Renders annotating tags with their entrypoint are not cached, as they
+    /// depend on the order of the entrypoints.
+    pub fn cache_rendered(mut self, cache: bool) -> Self {
+        self.cache_rendered = cache;
+        self
+    }
+
+    /// Sets the key of the combined stylesheet Vite emits with
+    /// `build.cssCodeSplit` disabled, e.g. `"style.css"`, which is included
+    /// along with every script entrypoint.
+    pub fn single_css<S: Into<String>>(mut self, key: Option<S>) -> Self {
+        self.single_css = key.map(Into::into);
+        self
+    }
+
+    /// Sets whether the development tags are rendered in production, should
+    /// the manifest fail to load, instead of failing, so that pages at least
+    /// load against the dev server. Disabled by default.
+    pub fn fallback_to_dev(mut self, fallback: bool) -> Self {
+        self.fallback_to_dev = fallback;
+        self
+    }
+
+    /// Sets patterns of files omitted from the resolved resources, e.g. an
+    /// asset loaded separately. A resource is omitted if its file contains
+    /// any of the patterns, hence suffixes such as `"polyfills.js"` suffice.
+    pub fn exclude(mut self, patterns: Vec<String>) -> Self {
+        self.exclude = patterns;
+        self
+    }
+
+    /// Sets whether stylesheets are loaded asynchronously, i.e. preloaded and
+    /// applied once loaded, along with a `<noscript>` fallback for clients
+    /// without JavaScript. Disabled by default.
+    pub fn async_css(mut self, enable: bool) -> Self {
+        self.async_css = enable;
+        self
+    }
+
+    /// Sets the paths of further manifests, e.g. of a separate vendor build,
+    /// merged into the manifest so that imports across builds resolve.
+    ///
+    /// Keys already present are not overridden, hence the manifest takes
+    /// precedence, followed by the additional manifests in order.
+    pub fn additional_manifests(mut self, paths: Vec<String>) -> Self {
+        self.additional_manifests = paths;
+        self
+    }
+
+    /// Sets how stylesheets are included in production. Defaults to
+    /// `ViteCssMode::Link`.
+    pub fn css_mode(mut self, mode: ViteCssMode) -> Self {
+        self.css_mode = mode;
+        self
+    }
+
+    /// Sets whether the HTML rendered by the tera and minijinja integrations
+    /// is marked safe, i.e. exempt from auto-escaping. Enabled by default;
+    /// disable it to have the output escaped, e.g. if it's embedded into JSON.
+    pub fn mark_safe(mut self, safe: bool) -> Self {
+        self.mark_safe = safe;
+        self
+    }
+
+    /// Sets the separator the rendered tags are joined with. Defaults to
+    /// `"\n"`.
+    pub fn line_separator<S: Into<String>>(mut self, separator: S) -> Self {
+        self.line_separator = separator.into();
+        self
+    }
+
+    /// Sets whether the stylesheets of imported chunks are preloaded using
+    /// `<link rel="preload" as="style">` instead of being linked, deferring
+    /// their application. The entrypoint's own stylesheets remain linked.
+    /// Disabled by default.
+    pub fn preload_import_css(mut self, preload: bool) -> Self {
+        self.preload_import_css = preload;
+        self
+    }
+
+    /// Sets whether the modules of entrypoints are preloaded using
+    /// `modulepreload` links instead of being executed, so that the
+    /// application decides when to `import()` them. Disabled by default.
+    pub fn entry_as_preload(mut self, preload: bool) -> Self {
+        self.entry_as_preload = preload;
+        self
+    }
+
+    /// Sets whether the legacy chunks emitted by `@vitejs/plugin-legacy` are
+    /// included along with the modern ones, i.e. the polyfills including
+    /// SystemJS and the `-legacy` counterparts of the entrypoints, loaded
+    /// using `nomodule` scripts. Disabled by default.
+    pub fn legacy(mut self, legacy: bool) -> Self {
+        self.legacy = legacy;
+        self
+    }
+
+    /// Sets the origin modules shared across micro-frontends are served
+    /// from, e.g. `https://shared.example.com`. Files of the manifest
+    /// starting with `shared_prefix` are loaded from it instead of `base` and
+    /// are always requested with `crossorigin`. Disabled by default.
+    pub fn shared_origin<S: Into<String>>(mut self, origin: Option<S>) -> Self {
+        self.shared_origin = origin.map(Into::into);
+        self
+    }
+
+    /// Sets the prefix identifying the files served from `shared_origin`.
+    /// Defaults to `shared/`.
+    pub fn shared_prefix<S: Into<String>>(mut self, prefix: S) -> Self {
+        self.shared_prefix = prefix.into();
+        self
+    }
+
+    /// Sets whether entrypoints are resolved regardless of their case, e.g.
+    /// `Views/Foo.js` resolving to the chunk `views/foo.js`, should template
+    /// authors rely on a case-insensitive filesystem. Exact matches take
+    /// precedence. Disabled by default, as the keys of the manifest are
+    /// case-sensitive.
+    pub fn case_insensitive_lookup(mut self, enabled: bool) -> Self {
+        self.case_insensitive_lookup = enabled;
+        self
+    }
+
+    /// Sets how long a single attempt to connect to the development server
+    /// may take before it is considered unavailable, as done by
+    /// `dev_server_available` and `wait_for_dev_server`. Defaults to 500ms.
+    pub fn dev_check_timeout(mut self, timeout: std::time::Duration) -> Self {
+        self.dev_check_timeout = timeout;
+        self
+    }
+
+    /// Sets how often `dev_server_available` retries connecting to the
+    /// development server before it is considered unavailable. Defaults to 3.
+    pub fn dev_check_retries(mut self, retries: u32) -> Self {
+        self.dev_check_retries = retries;
+        self
+    }
+
+    /// Sets whether the rendered tags are wrapped in comments noting the mode
+    /// and entrypoints they were rendered for, e.g.
+    /// `<!-- in-vite: mode=production entries=views/foo.js -->` and
+    /// `<!-- /in-vite -->`, to debug rendered pages. Disabled by default.
+    pub fn debug_comments(mut self, enabled: bool) -> Self {
+        self.debug_comments = enabled;
+        self
+    }
+
+    /// Sets whether entrypoints lacking from the manifest are served by the
+    /// development server at `host` in production, while the others are
+    /// included as built, e.g. to migrate to production builds gradually.
+    /// Served entrypoints are always rendered after the built ones, regardless
+    /// of the order requested. Disabled by default.
+    pub fn per_entry_dev_fallback(mut self, fallback: bool) -> Self {
+        self.per_entry_dev_fallback = fallback;
+        self
+    }
+
+    /// Sets the `rel` and `title` of stylesheets, named by their file in the
+    /// manifest or a suffix thereof, overriding `rel="stylesheet"`, e.g. to
+    /// render alternate stylesheets for theme switching. If several suffixes
+    /// match a stylesheet, the longest one wins.
+    pub fn stylesheet_overrides(
+        mut self,
+        overrides: HashMap<String, ViteStylesheetOptions>,
+    ) -> Self {
+        self.stylesheet_overrides = overrides.into_iter().collect();
+        self
+    }
+
+    /// Sets the values of the environment variables inspected by
+    /// `guess_mode`, which resolve to production, e.g. `staging` or `qa`.
+    /// Defaults to `production`.
+    ///
+    /// As `ViteOptions::default()` guesses the mode already, call
+    /// `guess_mode` afterwards to guess it anew.
+    pub fn production_envs(mut self, envs: Vec<String>) -> Self {
+        self.production_envs = envs;
+        self
+    }
+
+    /// Sets the `referrerpolicy` attribute applied to every tag rendered in
+    /// production, e.g. `no-referrer` for resources served from a CDN.
+    /// Disabled by default.
+    pub fn referrer_policy(mut self, policy: Option<&str>) -> Self {
+        self.referrer_policy = policy.map(str::to_string);
+        self
+    }
+
+    /// Sets a function transforming the URL of every resource in production,
+    /// i.e. after the base is prefixed, e.g. to shard resources across CDNs or
+    /// to sign them. Applies to rendered tags as well as to the URLs returned,
+    /// e.g. by `resolve` and `link_headers`.
+    pub fn url_transform(mut self, transform: Box<dyn Fn(&str) -> String + Send + Sync>) -> Self {
+        self.url_transform = Some(UrlTransform(transform.into()));
+        self
+    }
+
+    /// Sets the name of the environment, as introduced by Vite 6's
+    /// Environment API, whose manifest is selected from a manifest grouping
+    /// the manifests of several environments by name, e.g. `client`. Applies
+    /// within `manifest_pointer`, if set.
+    pub fn environment(mut self, name: &str) -> Self {
+        self.environment = Some(name.to_string());
         self
     }
 
     /// Sets the mode in which resources should be included.
     pub fn mode(mut self, mode: ViteMode) -> Self {
         self.mode = mode;
+        self.mode_guessed = false;
+        self
+    }
+
+    /// Sets the directory Vite serves sources from in development.
+    ///
+    /// When set and accessible, development entrypoints are checked for
+    /// existence beneath it and a warning is emitted for each missing one.
+    pub fn dev_root<S: Into<String>>(mut self, root: S) -> Self {
+        self.dev_root = Some(root.into());
         self
     }
 
@@ -70,33 +754,156 @@ impl ViteOptions {
     /// - `LOCO_ENV`
     /// - `RAILS_ENV`
     /// - `NODE_ENV`
+    /// - `APP_ENV`
     ///
-    /// and checks whether they evaluate to `development` or `production`.
-    /// If neither can be found, assumes `development`.
+    /// and checks whether they evaluate to any of `production_envs`, i.e.
+    /// `production` by default. Any other value resolves to `development`,
+    /// as does the absence of all of them.
     ///
+    /// `ViteOptions::default()` guesses the mode already; use `no_guess` to
+    /// opt out.
     pub fn guess_mode(mut self) -> Self {
         let mode = std::env::var("LOCO_ENV")
             .or_else(|_| std::env::var("RAILS_ENV"))
-            .or_else(|_| std::env::var("NODE_ENV"));
+            .or_else(|_| std::env::var("NODE_ENV"))
+            .or_else(|_| std::env::var("APP_ENV"));
 
         let mode = mode.unwrap_or("development".to_string());
-        self.mode = match mode.as_str() {
-            "production" => ViteMode::Production,
-            _ => ViteMode::Development,
+        self.mode = if self.production_envs.contains(&mode) {
+            ViteMode::Production
+        } else {
+            ViteMode::Development
         };
+        self.mode_guessed = true;
+
+        self
+    }
+
+    /// Resets the mode to `development` if it was guessed from the environment,
+    /// e.g. by `ViteOptions::default()`, for deterministic tests. A mode set
+    /// explicitly is kept, regardless of the order of calls.
+    pub fn no_guess(mut self) -> Self {
+        if self.mode_guessed {
+            self.mode = ViteMode::Development;
+            self.mode_guessed = false;
+        }
 
         self
     }
+
+    /// Returns the configured host of the development server.
+    ///
+    /// The getters are prefixed with `get_`, as the builder methods already
+    /// occupy the plain names.
+    pub fn get_host(&self) -> &str {
+        &self.host
+    }
+
+    /// Returns the configured mode.
+    pub fn get_mode(&self) -> &ViteMode {
+        &self.mode
+    }
+
+    /// Returns the configured path of the manifest.
+    pub fn get_manifest_path(&self) -> &str {
+        &self.manifest_path
+    }
+
+    /// Returns the configured source of the manifest, if any.
+    pub fn get_manifest_source(&self) -> Option<&str> {
+        self.manifest_source.as_deref()
+    }
+}
+
+/// Constructs the default options in production mode with the given manifest
+/// as source, e.g. for tests.
+impl From<String> for ViteOptions {
+    fn from(manifest: String) -> Self {
+        ViteOptions::default()
+            .mode(ViteMode::Production)
+            .source(Some(manifest))
+    }
+}
+
+/// Constructs the default options in production mode with the given manifest
+/// as source, e.g. for tests.
+impl From<&str> for ViteOptions {
+    fn from(manifest: &str) -> Self {
+        ViteOptions::from(manifest.to_string())
+    }
 }
 
 /// Encapsulates the configuration and logic required for resolving resources
 /// bundled by vite.
-#[derive(Debug)]
+///
+/// Clones share the cached manifest, so that a single instance can be
+/// registered under multiple names, e.g. as function and test.
+#[derive(Debug, Clone)]
 pub struct Vite {
     host: String,
     manifest_source: Option<String>,
     manifest_path: String,
     mode: ViteMode,
+    dev_root: Option<String>,
+    base: String,
+    manifest_format: ManifestFormat,
+    dynamic_import_css: bool,
+
+    // The manifest is loaded once and cached until it is invalidated.
+    manifest: Arc<RwLock<Option<Arc<Manifest>>>>,
+    ssr_manifest: Arc<RwLock<Option<Arc<SsrManifest>>>>,
+
+    // The HTML rendered in production per host and list of entrypoints, if
+    // `cache_rendered` is enabled.
+    rendered: Arc<RwLock<RenderedCache>>,
+    allow_non_entry: bool,
+    defer_classic: bool,
+    manifest_pointer: Option<String>,
+    combine_entries: bool,
+    emit_preloads: bool,
+    fallback_entry: Option<String>,
+    preload_assets: bool,
+    xhtml: bool,
+    tag_entry_attribute: bool,
+    dev_extension: Option<String>,
+    out_dir: String,
+    inline_stylesheets: Vec<String>,
+    client_without_entries: bool,
+    crossorigin: ViteCrossOrigin,
+    ssr_manifest_source: Option<String>,
+    ssr_manifest_path: String,
+    turbo_track: bool,
+    manifest_reader: Option<ManifestReader>,
+    modulepreload_polyfill: bool,
+    client_attributes: Vec<(String, String)>,
+    cache_rendered: bool,
+    single_css: Option<String>,
+    fallback_to_dev: bool,
+    exclude: Vec<String>,
+    async_css: bool,
+    additional_manifests: Vec<String>,
+    css_mode: ViteCssMode,
+    mark_safe: bool,
+    line_separator: String,
+    preload_import_css: bool,
+    entry_as_preload: bool,
+    legacy: bool,
+    shared_origin: Option<String>,
+    shared_prefix: String,
+    case_insensitive_lookup: bool,
+
+    // Only consumed by the health checks requiring the feature `tokio`.
+    #[cfg_attr(not(feature = "tokio"), allow(dead_code))]
+    dev_check_timeout: std::time::Duration,
+    #[cfg_attr(not(feature = "tokio"), allow(dead_code))]
+    dev_check_retries: u32,
+    debug_comments: bool,
+    per_entry_dev_fallback: bool,
+    manifest_path_explicit: bool,
+    stylesheet_overrides: BTreeMap<String, ViteStylesheetOptions>,
+    referrer_policy: Option<String>,
+    url_transform: Option<UrlTransform>,
+    environment: Option<String>,
 }
 
 impl Default for Vite {
@@ -112,9 +919,82 @@ impl<'a> Vite {
             manifest_source: opts.manifest_source,
             manifest_path: opts.manifest_path,
             mode: opts.mode,
+            dev_root: opts.dev_root,
+            base: opts.base,
+            manifest_format: opts.manifest_format,
+            dynamic_import_css: opts.dynamic_import_css,
+            manifest: Arc::new(RwLock::new(None)),
+            ssr_manifest: Arc::new(RwLock::new(None)),
+            rendered: Arc::default(),
+            allow_non_entry: opts.allow_non_entry,
+            defer_classic: opts.defer_classic,
+            manifest_pointer: opts.manifest_pointer,
+            combine_entries: opts.combine_entries,
+            emit_preloads: opts.emit_preloads,
+            fallback_entry: opts.fallback_entry,
+            preload_assets: opts.preload_assets,
+            xhtml: opts.xhtml,
+            tag_entry_attribute: opts.tag_entry_attribute,
+            dev_extension: opts.dev_extension,
+            out_dir: opts.out_dir,
+            inline_stylesheets: opts.inline_stylesheets,
+            client_without_entries: opts.client_without_entries,
+            crossorigin: opts.crossorigin,
+            ssr_manifest_source: opts.ssr_manifest_source,
+            ssr_manifest_path: opts.ssr_manifest_path,
+            turbo_track: opts.turbo_track,
+            manifest_reader: opts.manifest_reader,
+            modulepreload_polyfill: opts.modulepreload_polyfill,
+            client_attributes: opts.client_attributes,
+            cache_rendered: opts.cache_rendered,
+            single_css: opts.single_css,
+            fallback_to_dev: opts.fallback_to_dev,
+            exclude: opts.exclude,
+            async_css: opts.async_css,
+            additional_manifests: opts.additional_manifests,
+            css_mode: opts.css_mode,
+            mark_safe: opts.mark_safe,
+            line_separator: opts.line_separator,
+            preload_import_css: opts.preload_import_css,
+            entry_as_preload: opts.entry_as_preload,
+            legacy: opts.legacy,
+            shared_origin: opts.shared_origin,
+            shared_prefix: opts.shared_prefix,
+            case_insensitive_lookup: opts.case_insensitive_lookup,
+            dev_check_timeout: opts.dev_check_timeout,
+            dev_check_retries: opts.dev_check_retries,
+            debug_comments: opts.debug_comments,
+            per_entry_dev_fallback: opts.per_entry_dev_fallback,
+            manifest_path_explicit: opts.manifest_path_explicit,
+            stylesheet_overrides: opts.stylesheet_overrides,
+            referrer_policy: opts.referrer_policy,
+            url_transform: opts.url_transform,
+            environment: opts.environment,
         }
     }
 
+    /// Returns a shared instance using the default options, which is
+    /// initialized on first access.
+    ///
+    /// Thus, the mode is guessed from the environment only once; subsequent
+    /// changes to the environment are not reflected.
+    pub fn global() -> &'static Vite {
+        static GLOBAL: OnceLock<Vite> = OnceLock::new();
+        GLOBAL.get_or_init(Vite::default)
+    }
+
+    /// Creates an instance resolving resources from `manifest_json` in the
+    /// given mode, which consults neither the environment nor the file system
+    /// for the manifest. Intended for tests.
+    pub fn for_testing(manifest_json: &str, mode: ViteMode) -> Self {
+        let opts = ViteOptions::new()
+            .host("http://localhost:5173")
+            .mode(mode)
+            .source(Some(manifest_json));
+
+        Self::with_options(opts)
+    }
+
     pub fn host(&self) -> &str {
         &self.host
     }
@@ -123,79 +1003,3978 @@ impl<'a> Vite {
         &self.mode
     }
 
-    pub fn to_html(&'a self, entrypoints: Vec<&'a str>) -> Result<String, Error> {
-        if self.mode == ViteMode::Development {
-            return Ok(self.to_development_html(entrypoints));
-        }
-
-        let manifest: Manifest = match &self.manifest_source {
-            Some(manifest) => serde_json::from_str(&manifest)?,
-            None => {
-                let file = std::fs::File::open(&self.manifest_path)?;
-                serde_json::from_reader(file)?
-            }
-        };
-
-        let mut resources: Vec<Resource> = entrypoints
-            .iter()
-            .map(|entrypoint| manifest.resolve_resources(entrypoint))
-            .flatten()
-            .collect();
+    pub fn base(&self) -> &str {
+        &self.base
+    }
 
-        resources.sort();
-        let html = resources
-            .into_iter()
-            .map(|resource| resource.to_html())
-            .collect::<Vec<String>>()
-            .join("\n");
+    /// Returns the path the manifest is loaded from, unless a source or
+    /// reader is set.
+    pub fn manifest_path(&self) -> &str {
+        &self.manifest_path
+    }
 
-        Ok(html)
+    /// Returns whether the manifest has been loaded successfully and is
+    /// cached, e.g. to report readiness. Loading is deferred until first
+    /// required, hence it returns `false` beforehand and after invalidation.
+    pub fn manifest_loaded(&self) -> bool {
+        self.manifest
+            .read()
+            .unwrap_or_else(PoisonError::into_inner)
+            .is_some()
     }
 
-    fn to_development_html(&'a self, entrypoints: Vec<&'a str>) -> String {
-        let host = &self.host;
-        let mut lines: Vec<String> = vec![format!(
-            r#"<script type="module" src="{host}/@vite/client"></script>"#
-        )];
+    /// Returns whether the HTML rendered by the template integrations is
+    /// marked safe.
+    pub fn mark_safe(&self) -> bool {
+        self.mark_safe
+    }
 
-        entrypoints
-            .iter()
-            .map(|entry| format!(r#"<script type="module" src="{host}/{entry}"></script>"#))
-            .for_each(|line| lines.push(line));
+    /// Returns whether classic scripts, e.g. UMD builds, are deferred.
+    pub fn defer_classic(&self) -> bool {
+        self.defer_classic
+    }
 
-        lines.join("\n")
+    /// Returns a small banner advising to start Vite's development server,
+    /// which is only revealed if `@vite/client` fails to load.
+    ///
+    /// In production an empty string is returned.
+    pub fn dev_banner_html(&self) -> String {
+        ViteDevBanner::new(&self.host, &self.mode)
+            .base(self.development_base())
+            .dev_banner_html()
     }
-}
 
-#[derive(Debug)]
-pub struct ViteReactRefresh {
-    host: String,
-    mode: ViteMode,
-}
+    /// Returns the URL of the websocket the dev server pushes hot module
+    /// replacement updates through.
+    ///
+    /// In production an empty string is returned.
+    pub fn hmr_url(&self) -> String {
+        ViteHmrUrl::new(&self.host, &self.mode)
+            .base(self.development_base())
+            .hmr_url()
+    }
 
-impl ViteReactRefresh {
-    pub fn new<S: AsRef<str>>(host: S, mode: &ViteMode) -> Self {
-        Self {
-            host: host.as_ref().to_owned(),
-            mode: mode.to_owned(),
-        }
+    /// Returns a `<base>` tag pointing at the configured base.
+    ///
+    /// If the base is unset, i.e. `/`, an empty string is returned.
+    pub fn base_tag(&self) -> String {
+        ViteBaseTag::new(&self.base).base_tag()
     }
 
-    pub fn react_refresh(&self) -> String {
+    pub fn to_html(&'a self, entrypoints: Vec<&'a str>) -> Result<String, Error> {
+        self.render_html(entrypoints, None)
+    }
+
+    /// Renders the tags required to include the given entrypoints, split into
+    /// those belonging into `<head>`, i.e. stylesheets and preloads, and those
+    /// belonging into `<body>`, i.e. scripts. Displaying the result yields the
+    /// output of `to_html`.
+    pub fn render(&self, entrypoints: Vec<&str>) -> Result<ViteTags, Error> {
+        let html = self.to_html(entrypoints.clone())?;
+
+        // Tags are split by a separator, which cannot occur within them. The
+        // rendered HTML is cached per host and entrypoints only, hence the
+        // splitting renderer must not share the cache.
+        let splitting = Vite {
+            line_separator: "\0".to_string(),
+            rendered: Arc::default(),
+            ..self.clone()
+        };
+
+        let tags = splitting.to_html(entrypoints)?;
+
+        // Classic and module scripts belong into the body, whereas inline
+        // scripts, i.e. the modulepreload polyfill, must precede the preloads.
+        let (body, head): (Vec<&str>, Vec<&str>) = tags
+            .split('\0')
+            .filter(|tag| !tag.is_empty())
+            .partition(|tag| tag.starts_with("<script") && !tag.starts_with("<script>"));
+
+        Ok(ViteTags {
+            head: head.join(&self.line_separator),
+            body: body.join(&self.line_separator),
+            html,
+        })
+    }
+
+    /// Like `to_html`, but renders the tags in the given mode regardless of the
+    /// configured one, e.g. to force development tags on debug routes.
+    pub fn to_html_with_mode(
+        &self,
+        entrypoints: Vec<&str>,
+        mode: ViteMode,
+    ) -> Result<String, Error> {
+        // Only production renders are cached, regardless of the configured
+        // mode, hence the cache can be shared.
+        let vite = Vite {
+            mode,
+            ..self.clone()
+        };
+
+        vite.to_html(entrypoints)
+    }
+
+    /// Like `to_html`, but joins the tags without any separator regardless
+    /// of `line_separator`, e.g. to embed them into HTTP headers or JSON.
+    pub fn to_html_compact(&self, entrypoints: Vec<&str>) -> Result<String, Error> {
+        // The rendered HTML is cached per host and entrypoints only, hence the
+        // compact renderer must not share the cache.
+        let compact = Vite {
+            line_separator: String::new(),
+            rendered: Arc::default(),
+            ..self.clone()
+        };
+
+        compact.to_html(entrypoints)
+    }
+
+    /// Like `to_html`, but loads resources from the given host for this
+    /// render only.
+    ///
+    /// In development the host replaces the dev server's host, in production
+    /// it is prepended to the base, unless the base is an absolute URL.
+    pub fn to_html_with_host(
+        &'a self,
+        entrypoints: Vec<&'a str>,
+        host: &str,
+    ) -> Result<String, Error> {
+        self.render_html(entrypoints, Some(host))
+    }
+
+    /// Writes the HTML `to_html` renders directly into `w`.
+    ///
+    /// Unlike `to_html`, tags are formatted straight into `w` rather than into
+    /// a returned string, e.g. to reuse a buffer in hot template loops. Only
+    /// owned output allocates, i.e. renders cached by `cache_rendered`, URLs
+    /// returned by `url_transform`, inlined stylesheets and the lookup of
+    /// `legacy` chunks.
+    ///
+    /// As for benchmarks, compare against `to_html` with the entrypoints of
+    /// the actual pages: the saving is the returned `String` and its growth
+    /// per call, whereas resolving against the manifest costs the same.
+    pub fn write_html<W: Write>(
+        &'a self,
+        w: &mut W,
+        entrypoints: Vec<&'a str>,
+    ) -> Result<(), Error> {
+        self.write_html_with_host(w, entrypoints, None)
+    }
+
+    fn render_html(
+        &'a self,
+        entrypoints: Vec<&'a str>,
+        host: Option<&str>,
+    ) -> Result<String, Error> {
+        let mut html = String::new();
+        self.write_html_with_host(&mut html, entrypoints, host)?;
+
+        Ok(html)
+    }
+
+    fn write_html_with_host<W: Write>(
+        &'a self,
+        w: &mut W,
+        entrypoints: Vec<&'a str>,
+        host: Option<&str>,
+    ) -> Result<(), Error> {
+        if !self.debug_comments {
+            return self.write_tags(w, entrypoints, host);
+        }
+
+        let mode = match self.mode {
+            ViteMode::Development => "development",
+            ViteMode::Production => "production",
+        };
+
+        write!(w, "<!-- in-vite: mode={mode} entries=")?;
+        for (i, entry) in entrypoints.iter().enumerate() {
+            if i > 0 {
+                w.write_char(',')?;
+            }
+
+            w.write_str(entry)?;
+        }
+        w.write_str(" -->")?;
+        w.write_str(&self.line_separator)?;
+        self.write_tags(w, entrypoints, host)?;
+        w.write_str(&self.line_separator)?;
+        w.write_str("<!-- /in-vite -->")?;
+
+        Ok(())
+    }
+
+    fn write_tags<W: Write>(
+        &'a self,
+        w: &mut W,
+        entrypoints: Vec<&'a str>,
+        host: Option<&str>,
+    ) -> Result<(), Error> {
         if self.mode == ViteMode::Development {
-            let host = &self.host;
+            let host = host.unwrap_or(&self.host);
+            return Ok(self.write_development_html(w, entrypoints, host)?);
+        }
 
-            format!(
-                r#"<script type="module">
-import RefreshRuntime from "{host}/@react-refresh"
-RefreshRuntime.injectIntoGlobalHook(window)
-window.$RefreshReg$ = () => {{}}
-window.$RefreshSig$ = () => (type) => type
-window.__vite_plugin_react_preamble_installed__ = true
-</script>"#
-            )
+        if self.fallback_to_dev {
+            if let Err(err) = self.load_manifest() {
+                tracing::warn!("failed to load the manifest, falling back to development: {err}");
+                let host = host.unwrap_or(&self.host);
+                return Ok(self.write_development_html(w, entrypoints, host)?);
+            }
+        }
+
+        if self.per_entry_dev_fallback {
+            let manifest = self.load_manifest()?;
+            let opts = self.resolve_options();
+            let (built, served): (Vec<&str>, Vec<&str>) = entrypoints
+                .iter()
+                .partition(|entry| manifest.entry_chunk(entry, &opts).is_some());
+
+            if !served.is_empty() {
+                let mut built_html = Tracked { w, written: false };
+                self.write_production_html(&mut built_html, &built, host)?;
+                if built_html.written {
+                    w.write_str(&self.line_separator)?;
+                }
+
+                let host = host.unwrap_or(&self.host);
+                return Ok(self.write_development_html(w, served, host)?);
+            }
+        }
+
+        if !self.cache_rendered || self.tag_entry_attribute {
+            return self.write_production_html(w, &entrypoints, host);
+        }
+
+        // The order of the entrypoints determines the cascade, hence is part
+        // of the key.
+        let key = format!("{}\0{}", host.unwrap_or_default(), entrypoints.join("\0"));
+
+        let generation = {
+            let rendered = self.rendered.read().unwrap_or_else(PoisonError::into_inner);
+            if let Some(html) = rendered.html.get(&key) {
+                return Ok(w.write_str(html)?);
+            }
+
+            rendered.generation
+        };
+
+        let mut html = String::new();
+        self.write_production_html(&mut html, &entrypoints, host)?;
+        w.write_str(&html)?;
+        self.cache_rendered_html(generation, key, html);
+
+        Ok(())
+    }
+
+    /// Caches the HTML rendered for `key`, unless the manifest was invalidated
+    /// since the render started at `generation`, as the HTML may stem from
+    /// the previous manifest.
+    fn cache_rendered_html(&self, generation: u64, key: String, html: String) {
+        let mut rendered = self
+            .rendered
+            .write()
+            .unwrap_or_else(PoisonError::into_inner);
+        if rendered.generation == generation {
+            rendered.html.insert(key, html);
+        }
+    }
+
+    fn write_production_html<W: Write>(
+        &'a self,
+        w: &mut W,
+        entrypoints: &[&'a str],
+        host: Option<&str>,
+    ) -> Result<(), Error> {
+        let opts = self.tag_options(host);
+        let shared = self.shared_tag_options(&opts);
+        let manifest = self.load_manifest()?;
+        let resources = self.resolve_manifest_resources_by_entry(&manifest, entrypoints);
+        let cors = opts.cors();
+        let legacy = self.legacy_entries(&manifest, entrypoints);
+        if let (false, Some(polyfills)) = (legacy.is_empty(), manifest.chunk(LEGACY_POLYFILLS)) {
+            let url = opts.url(&polyfills.file);
+            write!(
+                w,
+                r#"<script nomodule{cors} id="vite-legacy-polyfill" src="{url}"></script>"#
+            )?;
+            w.write_str(&self.line_separator)?;
+        }
+
+        if self.modulepreload_polyfill && !resources.is_empty() {
+            write!(w, "<script>{MODULEPRELOAD_POLYFILL}</script>")?;
+            w.write_str(&self.line_separator)?;
+        }
+
+        let mut imported = false;
+        for (i, (resource, entry)) in resources.iter().enumerate() {
+            if let Some(path) = self.inlined_stylesheet_path(resource) {
+                if i > 0 {
+                    w.write_str(&self.line_separator)?;
+                }
+
+                let stylesheet = std::fs::read_to_string(path)?;
+                write!(w, "<style>{stylesheet}</style>")?;
+                continue;
+            }
+
+            // All imported stylesheets are written at once in place of the
+            // first; stylesheets precede all other resources.
+            let import =
+                self.css_mode == ViteCssMode::Import && matches!(resource, Resource::Stylesheet(_));
+            if import && imported {
+                continue;
+            }
+
+            if i > 0 {
+                w.write_str(&self.line_separator)?;
+            }
+
+            if import {
+                imported = true;
+                self.write_stylesheet_imports(w, &resources, &opts)?;
+                continue;
+            }
+
+            let opts = self.resource_tag_options(resource.uri(), &opts, &shared);
+
+            let stylesheet = self.stylesheet_override(resource);
+            let entry = self.tag_entry_attribute.then_some(*entry);
+            resource.write_html_with_entry(w, opts, entry, stylesheet)?;
+        }
+
+        for (i, file) in legacy.iter().enumerate() {
+            if i > 0 || !resources.is_empty() {
+                w.write_str(&self.line_separator)?;
+            }
+
+            let url = opts.url(file);
+            write!(
+                w,
+                r#"<script nomodule{cors}>System.import("{url}")</script>"#
+            )?;
+        }
+
+        Ok(())
+    }
+
+    /// Returns the URL of `uri` as rendered with `opts`, or with `shared` for
+    /// resources prefixed with `shared_prefix`.
+    fn resource_url(&self, uri: &str, opts: &TagOptions, shared: &Option<TagOptions>) -> String {
+        self.resource_tag_options(uri, opts, shared)
+            .url(uri)
+            .to_string()
+    }
+
+    /// Returns the files of the legacy counterparts of the given entrypoints,
+    /// e.g. `views/app-legacy.js` for `views/app.js`, if `legacy` is enabled.
+    fn legacy_entries<'m>(&self, manifest: &'m Manifest, entrypoints: &[&str]) -> Vec<&'m str> {
+        if !self.legacy {
+            return vec![];
+        }
+
+        entrypoints
+            .iter()
+            .filter_map(|entry| {
+                let (stem, extension) = entry.rsplit_once('.')?;
+                manifest.chunk(&format!("{stem}-legacy.{extension}"))
+            })
+            .map(|chunk| chunk.file.as_str())
+            .collect()
+    }
+
+    /// Writes a single `<style>` importing all stylesheets among `resources`,
+    /// which aren't inlined.
+    fn write_stylesheet_imports<W: Write>(
+        &self,
+        w: &mut W,
+        resources: &[(Resource, &str)],
+        opts: &TagOptions,
+    ) -> Result<(), Error> {
+        w.write_str("<style>")?;
+        for (resource, _) in resources.iter() {
+            if let Resource::Stylesheet(_) = resource {
+                if self.inlined_stylesheet_path(resource).is_none() {
+                    let url = opts.url(resource.uri());
+                    write!(w, r#"@import url("{url}");"#)?;
+                }
+            }
+        }
+
+        w.write_str("</style>")?;
+        Ok(())
+    }
+
+    /// Returns the options overriding how the resource is linked, if it is a
+    /// stylesheet named in `stylesheet_overrides`, preferring the longest
+    /// matching suffix.
+    fn stylesheet_override(&self, resource: &Resource) -> Option<&ViteStylesheetOptions> {
+        let Resource::Stylesheet(uri) = resource else {
+            return None;
+        };
+
+        self.stylesheet_overrides
+            .iter()
+            .filter(|(stylesheet, _)| uri.ends_with(stylesheet.as_str()))
+            .max_by_key(|(stylesheet, _)| stylesheet.len())
+            .map(|(_, opts)| opts)
+    }
+
+    /// Returns the path of the stylesheet within `out_dir`, if the resource is
+    /// a stylesheet to be inlined.
+    fn inlined_stylesheet_path(&self, resource: &Resource) -> Option<std::path::PathBuf> {
+        let Resource::Stylesheet(uri) = resource else {
+            return None;
+        };
+
+        let inline = self.css_mode == ViteCssMode::Inline
+            || self
+                .inline_stylesheets
+                .iter()
+                .any(|stylesheet| uri.ends_with(stylesheet.as_str()));
+        inline.then(|| std::path::Path::new(&self.out_dir).join(uri))
+    }
+
+    /// Returns the options resources are rendered with, taking an optional
+    /// host into account.
+    fn tag_options(&self, host: Option<&str>) -> TagOptions {
+        let base = self.production_base(host);
+        let crossorigin = match &self.crossorigin {
+            ViteCrossOrigin::Auto if is_cross_origin(&base, host) => ViteCrossOrigin::Anonymous,
+            ViteCrossOrigin::Auto => ViteCrossOrigin::Disabled,
+            crossorigin => crossorigin.clone(),
+        };
+
+        TagOptions {
+            base,
+            defer_classic: self.defer_classic,
+            xhtml: self.xhtml,
+            crossorigin,
+            turbo_track: self.turbo_track,
+            async_css: self.async_css,
+            referrer_policy: self.referrer_policy.clone(),
+            line_separator: self.line_separator.clone(),
+            url_transform: self.url_transform.clone(),
+        }
+    }
+
+    /// Returns the options shared resources are rendered with, i.e. prefixed
+    /// with `shared_origin` and requested with `crossorigin`, if configured.
+    fn shared_tag_options(&self, opts: &TagOptions) -> Option<TagOptions> {
+        let origin = self.shared_origin.as_deref()?;
+        Some(TagOptions {
+            base: format!("{}/", origin.trim_end_matches('/')),
+            crossorigin: match &opts.crossorigin {
+                ViteCrossOrigin::Disabled => ViteCrossOrigin::Anonymous,
+                crossorigin => crossorigin.clone(),
+            },
+            ..opts.clone()
+        })
+    }
+
+    /// Returns the options the resource at `uri` is rendered with, i.e.
+    /// `shared` for resources prefixed with `shared_prefix`, if configured.
+    /// Every path producing URLs of resources goes through it.
+    fn resource_tag_options<'o>(
+        &self,
+        uri: &str,
+        opts: &'o TagOptions,
+        shared: &'o Option<TagOptions>,
+    ) -> &'o TagOptions {
+        match shared {
+            Some(shared) if uri.starts_with(&self.shared_prefix) => shared,
+            _ => opts,
+        }
+    }
+
+    /// Returns the base production URLs are prefixed with, taking an
+    /// optional host into account.
+    fn production_base(&self, host: Option<&str>) -> String {
+        match host {
+            Some(host) if self.base.starts_with('/') => {
+                format!("{}{}", host.trim_end_matches('/'), self.base)
+            }
+            _ => self.base.clone(),
+        }
+    }
+
+    /// Returns the path the development server serves from. Only a path base
+    /// is respected, since Vite ignores the origin of URL bases during
+    /// development.
+    fn development_base(&self) -> &str {
+        if self.base.starts_with('/') {
+            &self.base
         } else {
-            "".to_string()
+            "/"
         }
     }
+
+    /// Renders the HTML required to include every entrypoint of the manifest
+    /// matching any of the given glob patterns, e.g. `admin/*.js`.
+    ///
+    /// Entrypoints are expanded against the manifest, hence a manifest is
+    /// required even in development.
+    #[cfg(feature = "glob")]
+    pub fn to_html_glob(&'a self, patterns: &[&str]) -> Result<String, Error> {
+        let patterns = patterns
+            .iter()
+            .map(|pattern| glob::Pattern::new(pattern))
+            .collect::<Result<Vec<_>, _>>()?;
+
+        let manifest = self.load_manifest()?;
+        let entrypoints = manifest.entrypoints_matching(&patterns);
+
+        self.to_html(entrypoints)
+    }
+
+    /// Returns the logical name of the chunk with the given key, i.e. its
+    /// unhashed name as `foo` for `assets/foo-BRBmoGS9.js`, e.g. for logging,
+    /// or `None` if the manifest lacks the chunk.
+    pub fn logical_name(&self, entry: &str) -> Result<Option<String>, Error> {
+        let manifest = self.load_manifest()?;
+        Ok(manifest
+            .chunk(entry)
+            .map(|chunk| chunk.logical_name().to_string()))
+    }
+
+    /// Returns the metadata of the chunk with the given key, e.g. its sizes
+    /// as reported by Vite, or `None` if the manifest lacks the chunk.
+    pub fn chunk_metadata(&self, key: &str) -> Result<Option<ChunkMetadata>, Error> {
+        let manifest = self.load_manifest()?;
+        Ok(manifest.chunk(key).map(|chunk| chunk.metadata()))
+    }
+
+    /// Compares the manifest against the `previous` one, given as source in
+    /// `manifest_format`, and returns the URLs of the files which were added,
+    /// removed or changed, e.g. to feed a service worker's precache manifest.
+    pub fn diff_manifest(&self, previous: &str) -> Result<ManifestDiff, Error> {
+        let manifest = self.load_manifest()?;
+        let previous = self
+            .manifest_format
+            .deserialize(previous, self.manifest_pointer().as_deref())?;
+
+        let opts = self.tag_options(None);
+        let shared = self.shared_tag_options(&opts);
+        let urls = |files: Vec<&str>| -> Vec<String> {
+            files
+                .into_iter()
+                .map(|file| self.resource_url(file, &opts, &shared))
+                .collect()
+        };
+
+        let (added, removed, changed) = manifest.diff(&previous);
+        Ok(ManifestDiff {
+            added: urls(added),
+            removed: urls(removed),
+            changed: urls(changed),
+        })
+    }
+
+    /// Returns the URLs of all assets listed as chunks of their own instead of
+    /// by the chunks referencing them, e.g. fonts referenced only by `url()`
+    /// within stylesheets, to preload critical ones.
+    pub fn orphan_assets(&self) -> Result<Vec<String>, Error> {
+        let manifest = self.load_manifest()?;
+        let opts = self.tag_options(None);
+        let shared = self.shared_tag_options(&opts);
+        let assets = manifest
+            .orphan_assets()
+            .into_iter()
+            .map(|asset| self.resource_url(asset, &opts, &shared))
+            .collect();
+
+        Ok(assets)
+    }
+
+    /// Returns the major version of Vite the manifest was presumably produced
+    /// by, e.g. to surface mismatches during support triage. It is a best
+    /// effort heuristic: manifests loaded from within `.vite` or reporting
+    /// chunk names stem from Vite 5, others are assumed to stem from Vite 4.
+    pub fn detect_manifest_version(&self) -> Result<u8, Error> {
+        let manifest = self.load_manifest()?;
+        let from_file = self.manifest_source.is_none() && self.manifest_reader.is_none();
+        let in_vite_dir = from_file
+            && self
+                .locate_manifest()
+                .components()
+                .any(|component| component.as_os_str() == ".vite");
+
+        if in_vite_dir || manifest.has_chunk_names() {
+            return Ok(5);
+        }
+
+        Ok(4)
+    }
+
+    /// Returns the URLs of all stylesheets across the whole build, regardless
+    /// of the entrypoints including them, e.g. to audit them.
+    pub fn all_stylesheets(&self) -> Result<Vec<String>, Error> {
+        let manifest = self.load_manifest()?;
+        let opts = self.tag_options(None);
+        let shared = self.shared_tag_options(&opts);
+        let stylesheets = manifest
+            .stylesheets()
+            .into_iter()
+            .map(|stylesheet| self.resource_url(stylesheet, &opts, &shared))
+            .collect();
+
+        Ok(stylesheets)
+    }
+
+    /// Loads the manifest, unless cached already, and passes it to `f`, e.g.
+    /// to walk the graph of chunks.
+    pub fn with_manifest<R>(&self, f: impl FnOnce(&Manifest) -> R) -> Result<R, Error> {
+        let manifest = self.load_manifest()?;
+        Ok(f(&manifest))
+    }
+
+    /// Returns whether the given entrypoint includes any stylesheet.
+    ///
+    /// In development stylesheets are injected by the dev server, hence it
+    /// returns `false` without consulting the manifest.
+    pub fn entry_has_css(&'a self, entry: &'a str) -> Result<bool, Error> {
+        if self.mode == ViteMode::Development {
+            return Ok(false);
+        }
+
+        let manifest = self.load_manifest()?;
+        let has_css = self
+            .resolve_manifest_resources(&manifest, &[entry])
+            .iter()
+            .any(|resource| matches!(resource, Resource::Stylesheet(_)));
+
+        Ok(has_css)
+    }
+
+    /// Checks whether the development server at `host` accepts connections,
+    /// retrying up to `dev_check_retries` times, each attempt bounded by
+    /// `dev_check_timeout`. Requires the feature `tokio`.
+    ///
+    /// In production there is no development server, hence it returns
+    /// `false`.
+    #[cfg(feature = "tokio")]
+    pub async fn dev_server_available(&self) -> bool {
+        use std::time::Duration;
+        use tokio::net::TcpStream;
+
+        if self.mode == ViteMode::Production {
+            return false;
+        }
+
+        let address = dev_server_address(&self.host);
+        for attempt in 0..=self.dev_check_retries {
+            if attempt > 0 {
+                tokio::time::sleep(Duration::from_millis(50)).await;
+            }
+
+            let connect = TcpStream::connect(address.as_str());
+            if let Ok(Ok(_)) = tokio::time::timeout(self.dev_check_timeout, connect).await {
+                return true;
+            }
+        }
+
+        false
+    }
+
+    /// Waits until the development server at `host` accepts connections, e.g.
+    /// to defer serving pages until Vite started. Attempts, each bounded by
+    /// `dev_check_timeout`, are backed off exponentially until `timeout`
+    /// elapses. Requires the feature `tokio`.
+    ///
+    /// In production there is no development server, hence it returns
+    /// immediately.
+    #[cfg(feature = "tokio")]
+    pub async fn wait_for_dev_server(&self, timeout: std::time::Duration) -> Result<(), Error> {
+        use std::time::Duration;
+        use tokio::net::TcpStream;
+        use tokio::time::Instant;
+
+        if self.mode == ViteMode::Production {
+            return Ok(());
+        }
+
+        let address = dev_server_address(&self.host);
+        let deadline = Instant::now() + timeout;
+        let mut delay = Duration::from_millis(50);
+        loop {
+            let now = Instant::now();
+            if now >= deadline {
+                return Err(Error::DevServerUnavailable(self.host.clone()));
+            }
+
+            let connect = TcpStream::connect(address.as_str());
+            let attempt = self.dev_check_timeout.min(deadline - now);
+            if let Ok(Ok(_)) = tokio::time::timeout(attempt, connect).await {
+                return Ok(());
+            }
+
+            tokio::time::sleep_until(deadline.min(Instant::now() + delay)).await;
+            delay = (delay * 2).min(Duration::from_secs(1));
+        }
+    }
+
+    /// Returns the URL of the built file of a worker entry, e.g. to pass it
+    /// to `new Worker(...)`, or `None` if the manifest lacks the entry.
+    ///
+    /// In development the worker is served by the dev server instead.
+    pub fn worker_url(&self, entry: &str) -> Result<Option<String>, Error> {
+        if self.mode == ViteMode::Development {
+            let host = &self.host;
+            let base = self.development_base();
+            return Ok(Some(format!("{host}{base}{entry}")));
+        }
+
+        let manifest = self.load_manifest()?;
+        let opts = self.tag_options(None);
+        let shared = self.shared_tag_options(&opts);
+        let url = manifest
+            .chunk(entry)
+            .map(|chunk| self.resource_url(&chunk.file, &opts, &shared));
+
+        Ok(url)
+    }
+
+    /// Returns the preloads of all fonts reachable from the given entry, e.g.
+    /// to speed up the largest contentful paint.
+    ///
+    /// In development fonts are served by the dev server, hence no preloads
+    /// are returned.
+    pub fn font_preloads(&'a self, entry: &'a str) -> Result<String, Error> {
+        let mut html = String::new();
+        if self.mode == ViteMode::Development {
+            return Ok(html);
+        }
+
+        let manifest = self.load_manifest()?;
+        let resolve_opts = ResolveOptions {
+            preload_assets: true,
+            ..self.resolve_options()
+        };
+
+        // Fonts may be referenced by several chunks.
+        let mut fonts: Vec<Resource> = vec![];
+        for resource in manifest.resolve_resources(entry, &resolve_opts) {
+            let is_font = match resource {
+                Resource::PreloadAsset(uri) => {
+                    matches!(preload_destination(uri), Some(("font", _)))
+                }
+                _ => false,
+            };
+
+            if is_font && !fonts.contains(&resource) {
+                fonts.push(resource);
+            }
+        }
+
+        let opts = self.tag_options(None);
+        let shared = self.shared_tag_options(&opts);
+        for (i, font) in fonts.iter().enumerate() {
+            if i > 0 {
+                html.push_str(&self.line_separator);
+            }
+
+            let opts = self.resource_tag_options(font.uri(), &opts, &shared);
+            font.write_html(&mut html, opts)?;
+        }
+
+        Ok(html)
+    }
+
+    /// Reports how each of the given entrypoints resolves against the
+    /// manifest, e.g. to find out why a page renders without assets.
+    ///
+    /// The manifest is consulted regardless of the mode.
+    pub fn diagnose(&'a self, entrypoints: Vec<&'a str>) -> Result<ResolutionReport, Error> {
+        let manifest = self.load_manifest()?;
+        let opts = self.resolve_options();
+        let entrypoints = entrypoints
+            .into_iter()
+            .map(|entry| (entry.to_string(), manifest.diagnose(entry, &opts)))
+            .collect();
+
+        Ok(ResolutionReport { entrypoints })
+    }
+
+    /// Returns a JSON document describing how the given entrypoints resolve in
+    /// production, i.e. the resources in the order they're rendered along with
+    /// their kind, URL, originating chunk and entrypoint, without rendering
+    /// any HTML.
+    pub fn explain(&'a self, entrypoints: Vec<&'a str>) -> Result<String, Error> {
+        let manifest = self.load_manifest()?;
+        let opts = self.tag_options(None);
+        let shared = self.shared_tag_options(&opts);
+        let resources: Vec<serde_json::Value> = self
+            .resolve_manifest_resources_by_entry(&manifest, &entrypoints)
+            .into_iter()
+            .map(|(resource, entry)| {
+                let kind = match resource.kind() {
+                    AssetKind::Stylesheet => "stylesheet",
+                    AssetKind::Module => "module",
+                    AssetKind::Script => "script",
+                    AssetKind::PreloadModule => "preload_module",
+                    AssetKind::PreloadStylesheet => "preload_stylesheet",
+                    AssetKind::PreloadAsset => "preload_asset",
+                };
+
+                serde_json::json!({
+                    "kind": kind,
+                    "url": self.resource_url(resource.uri(), &opts, &shared),
+                    "chunk": manifest.chunk_of(resource.uri()),
+                    "entrypoint": entry,
+                })
+            })
+            .collect();
+
+        let plan = serde_json::json!({
+            "entrypoints": entrypoints,
+            "resources": resources,
+        });
+
+        Ok(serde_json::to_string_pretty(&plan)?)
+    }
+
+    /// Returns a `<script type="application/json" id="vite-manifest">` holding
+    /// a map of every entrypoint to the URLs of its resources, in the order
+    /// they're rendered, e.g. for client-side routers to prefetch on
+    /// navigation.
+    ///
+    /// In development there is no manifest, hence an empty string is
+    /// returned.
+    pub fn client_manifest_script(&self) -> Result<String, Error> {
+        if self.mode == ViteMode::Development {
+            return Ok(String::new());
+        }
+
+        let manifest = self.load_manifest()?;
+        let opts = self.tag_options(None);
+        let shared = self.shared_tag_options(&opts);
+        let entries: serde_json::Map<String, serde_json::Value> = manifest
+            .iter()
+            .filter(|(_, chunk)| chunk.is_entry)
+            .map(|(key, _)| {
+                let urls: Vec<String> = self
+                    .resolve_manifest_resources_by_entry(&manifest, &[key])
+                    .into_iter()
+                    .map(|(resource, _)| self.resource_url(resource.uri(), &opts, &shared))
+                    .collect();
+
+                (key.to_string(), serde_json::json!(urls))
+            })
+            .collect();
+
+        // Escapes `</script>` within URLs, which would end the script early.
+        let json = serde_json::to_string(&entries)?.replace("</", "<\\/");
+        Ok(format!(
+            r#"<script type="application/json" id="vite-manifest">{json}</script>"#
+        ))
+    }
+
+    /// Resolves the assets required to include the given entrypoints, in the
+    /// order they would be rendered by `to_html`.
+    pub fn resolve(&'a self, entrypoints: Vec<&'a str>) -> Result<Vec<ResolvedAsset>, Error> {
+        if self.mode == ViteMode::Development {
+            if entrypoints.is_empty() && !self.client_without_entries {
+                return Ok(vec![]);
+            }
+
+            let host = &self.host;
+            let base = self.development_base();
+            let client = ResolvedAsset {
+                kind: AssetKind::Module,
+                url: format!("{host}{base}@vite/client"),
+            };
+
+            let assets = std::iter::once(client)
+                .chain(entrypoints.iter().map(|entry| ResolvedAsset {
+                    kind: AssetKind::Module,
+                    url: format!("{host}{base}{}", self.dev_entrypoint(entry)),
+                }))
+                .collect();
+
+            return Ok(assets);
+        }
+
+        let assets = self
+            .resolve_production_assets(&entrypoints)?
+            .into_iter()
+            .map(|(asset, _)| asset)
+            .collect();
+
+        Ok(assets)
+    }
+
+    /// Resolves the assets required to include the given entrypoints in
+    /// production, along with the CORS mode they're requested in, e.g.
+    /// `Anonymous` for shared resources.
+    fn resolve_production_assets(
+        &'a self,
+        entrypoints: &[&'a str],
+    ) -> Result<Vec<(ResolvedAsset, ViteCrossOrigin)>, Error> {
+        let manifest = self.load_manifest()?;
+        let opts = self.tag_options(None);
+        let shared = self.shared_tag_options(&opts);
+        let assets = self
+            .resolve_manifest_resources(&manifest, entrypoints)
+            .iter()
+            .map(|resource| {
+                let opts = self.resource_tag_options(resource.uri(), &opts, &shared);
+                let asset = ResolvedAsset {
+                    kind: resource.kind(),
+                    url: opts.url(resource.uri()).to_string(),
+                };
+
+                (asset, opts.crossorigin.clone())
+            })
+            .collect();
+
+        Ok(assets)
+    }
+
+    /// Renders the preloads of the chunks and stylesheets of the given
+    /// modules, e.g. the modules rendered during server-side rendering, using
+    /// the SSR manifest.
+    ///
+    /// In development modules are loaded by the dev server on demand, hence
+    /// no preloads are returned.
+    pub fn preload_for_modules(&self, module_ids: &[&str]) -> Result<String, Error> {
+        let mut html = String::new();
+        if self.mode == ViteMode::Development {
+            return Ok(html);
+        }
+
+        // The files of the SSR manifest are prefixed with the base already.
+        let opts = TagOptions {
+            base: String::new(),
+            ..self.tag_options(None)
+        };
+
+        let manifest = self.load_ssr_manifest()?;
+        for (i, resource) in manifest.resolve_resources(module_ids).iter().enumerate() {
+            if i > 0 {
+                html.push_str(&self.line_separator);
+            }
+
+            resource.write_html(&mut html, &opts)?;
+        }
+
+        Ok(html)
+    }
+
+    /// Renders `<link rel="prefetch">` tags for the modules and stylesheets of
+    /// the given entrypoints, e.g. to hint the assets of a probable next route
+    /// at low priority without executing them.
+    ///
+    /// In development modules are served by the dev server on demand, hence
+    /// no prefetches are returned.
+    pub fn prefetch(&'a self, entrypoints: Vec<&'a str>) -> Result<String, Error> {
+        let mut html = String::new();
+        if self.mode == ViteMode::Development {
+            return Ok(html);
+        }
+
+        let opts = self.tag_options(None);
+        let shared = self.shared_tag_options(&opts);
+        let manifest = self.load_manifest()?;
+
+        // Stylesheets may be both linked and preloaded.
+        let mut uris: Vec<&str> = vec![];
+        let mut resources = vec![];
+        for resource in self.resolve_manifest_resources(&manifest, &entrypoints) {
+            if resource.kind() != AssetKind::PreloadAsset && !uris.contains(&resource.uri()) {
+                uris.push(resource.uri());
+                resources.push(resource);
+            }
+        }
+
+        for (i, resource) in resources.iter().enumerate() {
+            if i > 0 {
+                html.push_str(&self.line_separator);
+            }
+
+            let opts = self.resource_tag_options(resource.uri(), &opts, &shared);
+            resource.write_prefetch(&mut html, opts)?;
+        }
+
+        Ok(html)
+    }
+
+    /// Returns the values of `Link` headers preloading the assets required to
+    /// include the given entrypoints, e.g. to send them as `103 Early Hints`
+    /// before the page is rendered.
+    pub fn link_headers(&'a self, entrypoints: Vec<&'a str>) -> Result<Vec<String>, Error> {
+        let assets = match self.mode {
+            ViteMode::Development => self
+                .resolve(entrypoints)?
+                .into_iter()
+                .map(|asset| (asset, ViteCrossOrigin::Disabled))
+                .collect(),
+            ViteMode::Production => self.resolve_production_assets(&entrypoints)?,
+        };
+
+        let headers = assets
+            .into_iter()
+            .map(|(asset, crossorigin)| {
+                let url = asset.url;
+                // Preloads are only reused if requested in the same CORS mode.
+                let cors = match crossorigin {
+                    ViteCrossOrigin::Anonymous => "; crossorigin",
+                    ViteCrossOrigin::UseCredentials => "; crossorigin=use-credentials",
+                    ViteCrossOrigin::Disabled | ViteCrossOrigin::Auto => "",
+                };
+                match asset.kind {
+                    AssetKind::Stylesheet | AssetKind::PreloadStylesheet => {
+                        format!("<{url}>; rel=preload; as=style{cors}")
+                    }
+                    AssetKind::Module | AssetKind::PreloadModule => {
+                        format!("<{url}>; rel=modulepreload{cors}")
+                    }
+                    AssetKind::Script => format!("<{url}>; rel=preload; as=script{cors}"),
+                    AssetKind::PreloadAsset => {
+                        match preload_destination(&url).unwrap_or(("fetch", true)) {
+                            (destination, true) if cors.is_empty() => {
+                                format!("<{url}>; rel=preload; as={destination}; crossorigin")
+                            }
+                            (destination, _) => {
+                                format!("<{url}>; rel=preload; as={destination}{cors}")
+                            }
+                        }
+                    }
+                }
+            })
+            .collect();
+
+        Ok(headers)
+    }
+
+    /// Clears the cached manifest, so that it is loaded anew from either
+    /// `manifest_source` or `manifest_path` during the next render. The SSR
+    /// manifest and rendered HTML are cleared likewise.
+    pub fn invalidate_manifest(&self) {
+        let mut manifest = self
+            .manifest
+            .write()
+            .unwrap_or_else(PoisonError::into_inner);
+        *manifest = None;
+
+        let mut ssr_manifest = self
+            .ssr_manifest
+            .write()
+            .unwrap_or_else(PoisonError::into_inner);
+        *ssr_manifest = None;
+
+        // Renders in flight must not cache HTML of the previous manifest.
+        let mut rendered = self
+            .rendered
+            .write()
+            .unwrap_or_else(PoisonError::into_inner);
+        rendered.generation += 1;
+        rendered.html.clear();
+    }
+
+    /// Returns the cached SSR manifest or loads and deserializes it from
+    /// either `ssr_manifest_source` or `ssr_manifest_path`.
+    fn load_ssr_manifest(&self) -> Result<Arc<SsrManifest>, Error> {
+        if let Some(manifest) = self
+            .ssr_manifest
+            .read()
+            .unwrap_or_else(PoisonError::into_inner)
+            .as_ref()
+        {
+            return Ok(manifest.clone());
+        }
+
+        let manifest: Arc<SsrManifest> = Arc::new(match &self.ssr_manifest_source {
+            Some(manifest) => self.manifest_format.deserialize_value(manifest)?,
+            None => {
+                let manifest = std::fs::read_to_string(&self.ssr_manifest_path)?;
+                self.manifest_format.deserialize_value(&manifest)?
+            }
+        });
+
+        let mut cached = self
+            .ssr_manifest
+            .write()
+            .unwrap_or_else(PoisonError::into_inner);
+        *cached = Some(manifest.clone());
+
+        Ok(manifest)
+    }
+
+    /// Returns the cached manifest or loads and deserializes it from either
+    /// `manifest_source`, `manifest_reader` or `manifest_path`.
+    fn load_manifest(&self) -> Result<Arc<Manifest>, Error> {
+        if let Some(manifest) = self
+            .manifest
+            .read()
+            .unwrap_or_else(PoisonError::into_inner)
+            .as_ref()
+        {
+            return Ok(manifest.clone());
+        }
+
+        let pointer = self.manifest_pointer();
+        let pointer = pointer.as_deref();
+        let mut manifest = match (&self.manifest_source, &self.manifest_reader) {
+            (Some(manifest), _) => self.manifest_format.deserialize(manifest, pointer)?,
+            (None, Some(reader)) => {
+                let reader = (reader.0)()?;
+                self.manifest_format.deserialize_reader(reader, pointer)?
+            }
+            (None, None) => {
+                let manifest = std::fs::read_to_string(self.locate_manifest())?;
+                self.manifest_format.deserialize(&manifest, pointer)?
+            }
+        };
+
+        for path in self.additional_manifests.iter() {
+            let additional = std::fs::read_to_string(path)?;
+            manifest.merge(self.manifest_format.deserialize(&additional, pointer)?);
+        }
+
+        let manifest = Arc::new(manifest);
+
+        let mut cached = self
+            .manifest
+            .write()
+            .unwrap_or_else(PoisonError::into_inner);
+        *cached = Some(manifest.clone());
+
+        Ok(manifest)
+    }
+
+    /// Returns the JSON pointer locating the manifest, i.e. `manifest_pointer`
+    /// followed by the name of the `environment`, if any.
+    fn manifest_pointer(&self) -> Option<String> {
+        let pointer = self.manifest_pointer.as_deref();
+        match (pointer, &self.environment) {
+            (pointer, Some(environment)) => {
+                // Escapes the name as reference token, see RFC 6901.
+                let environment = environment.replace('~', "~0").replace('/', "~1");
+                Some(format!("{}/{environment}", pointer.unwrap_or_default()))
+            }
+            (pointer, None) => pointer.map(str::to_string),
+        }
+    }
+
+    /// Returns the path of the manifest, falling back to the location of Vite
+    /// 4, i.e. outside of `.vite`, if `manifest_path` is left at its default
+    /// and absent.
+    fn locate_manifest(&self) -> std::path::PathBuf {
+        let path = std::path::Path::new(&self.manifest_path);
+        if self.manifest_path_explicit || path.exists() {
+            return path.to_path_buf();
+        }
+
+        let legacy = path
+            .parent()
+            .filter(|dir| dir.file_name() == Some(".vite".as_ref()))
+            .and_then(|dir| Some(dir.parent()?.join(path.file_name()?)))
+            .filter(|legacy| legacy.exists());
+
+        match legacy {
+            Some(legacy) => {
+                tracing::info!(
+                    "loading the manifest from Vite 4's location '{}'",
+                    legacy.display()
+                );
+                legacy
+            }
+            None => path.to_path_buf(),
+        }
+    }
+
+    /// Returns the options the manifest resolves resources with.
+    fn resolve_options(&self) -> ResolveOptions {
+        ResolveOptions {
+            dynamic_import_css: self.dynamic_import_css,
+            preload_import_css: self.preload_import_css,
+            entry_as_preload: self.entry_as_preload,
+            allow_non_entry: self.allow_non_entry,
+            emit_preloads: self.emit_preloads,
+            preload_assets: self.preload_assets,
+            single_css: self.single_css.clone(),
+            case_insensitive: self.case_insensitive_lookup,
+        }
+    }
+
+    /// Resolves the resources of all entrypoints in the order they're
+    /// included.
+    fn resolve_manifest_resources<'m>(
+        &'m self,
+        manifest: &'m Manifest,
+        entrypoints: &[&'m str],
+    ) -> Vec<Resource<'m>> {
+        self.resolve_manifest_resources_by_entry(manifest, entrypoints)
+            .into_iter()
+            .map(|(resource, _)| resource)
+            .collect()
+    }
+
+    /// Resolves the resources of all entrypoints in the order they're
+    /// included, along with the first entrypoint requiring them.
+    fn resolve_manifest_resources_by_entry<'m>(
+        &'m self,
+        manifest: &'m Manifest,
+        entrypoints: &[&'m str],
+    ) -> Vec<(Resource<'m>, &'m str)> {
+        let opts = self.resolve_options();
+        let mut resources: Vec<(Resource<'m>, &'m str)> = entrypoints
+            .iter()
+            .flat_map(|&entrypoint| {
+                let resources = manifest.resolve_resources(entrypoint, &opts);
+                let resources = match &self.fallback_entry {
+                    Some(fallback) if resources.is_empty() => {
+                        tracing::debug!(
+                            "entrypoint '{entrypoint}' resolved to no resources; falling back to '{fallback}'"
+                        );
+                        manifest.resolve_resources(fallback, &opts)
+                    }
+                    _ => resources,
+                };
+
+                resources
+                    .into_iter()
+                    .map(move |resource| (resource, entrypoint))
+            })
+            .collect();
+
+        // Resources shared by several entrypoints are included once, carrying
+        // the first one.
+        let mut seen = HashSet::new();
+        resources.retain(|(resource, _)| seen.insert(*resource));
+
+        resources.retain(|(resource, _)| {
+            !self
+                .exclude
+                .iter()
+                .any(|pattern| resource.uri().contains(pattern.as_str()))
+        });
+
+        // Stylesheets linked for one entrypoint need not be preloaded for
+        // another.
+        if self.preload_import_css {
+            let linked: Vec<&str> = resources
+                .iter()
+                .filter_map(|(resource, _)| match resource {
+                    Resource::Stylesheet(uri) => Some(*uri),
+                    _ => None,
+                })
+                .collect();
+            resources.retain(|(resource, _)| match resource {
+                Resource::PreloadStylesheet(uri) => !linked.contains(uri),
+                _ => true,
+            });
+        }
+
+        // Modules executed for one entrypoint, e.g. a shared chunk requested as
+        // entrypoint itself, need not be preloaded for another.
+        let executed: Vec<&str> = resources
+            .iter()
+            .filter_map(|(resource, _)| match resource {
+                Resource::Module(uri) => Some(*uri),
+                _ => None,
+            })
+            .collect();
+        resources.retain(|(resource, _)| match resource {
+            Resource::PreloadModule(uri) => !executed.contains(uri),
+            _ => true,
+        });
+
+        // The sort is stable, hence resources remain in the order of their
+        // entrypoints, unless ordered otherwise.
+        resources.sort_by(|(a, _), (b, _)| a.inclusion_order(b));
+
+        resources
+    }
+
+    fn write_development_html<W: Write>(
+        &'a self,
+        w: &mut W,
+        entrypoints: Vec<&'a str>,
+        host: &str,
+    ) -> std::fmt::Result {
+        if entrypoints.is_empty() && !self.client_without_entries {
+            return Ok(());
+        }
+
+        let entrypoints: Vec<Cow<str>> = entrypoints
+            .iter()
+            .map(|entry| self.dev_entrypoint(entry))
+            .collect();
+        let entrypoints: Vec<&str> = entrypoints.iter().map(AsRef::as_ref).collect();
+
+        for entrypoint in self.missing_dev_entrypoints(&entrypoints) {
+            tracing::warn!(
+                "development entrypoint '{entrypoint}' does not exist beneath '{}'",
+                self.dev_root.as_deref().unwrap_or_default()
+            );
+        }
+
+        let base = self.development_base();
+        let attrs: String = self
+            .client_attributes
+            .iter()
+            .map(|(key, value)| format!(r#" {key}="{}""#, Escaped(value)))
+            .collect();
+
+        if self.combine_entries {
+            write!(
+                w,
+                r#"<script type="module"{attrs}>import "{host}{base}@vite/client";"#
+            )?;
+            for entry in entrypoints {
+                write!(w, r#"import "{host}{base}{entry}";"#)?;
+            }
+
+            return w.write_str("</script>");
+        }
+
+        write!(
+            w,
+            r#"<script type="module" src="{host}{base}@vite/client"{attrs}></script>"#
+        )?;
+
+        for entry in entrypoints {
+            w.write_str(&self.line_separator)?;
+            write!(
+                w,
+                r#"<script type="module" src="{host}{base}{entry}"></script>"#
+            )?;
+        }
+
+        Ok(())
+    }
+
+    /// Returns the entrypoint as requested from the dev server, i.e. with
+    /// `dev_extension` appended if it lacks an extension.
+    fn dev_entrypoint<'e>(&self, entry: &'e str) -> Cow<'e, str> {
+        let name = entry.rsplit('/').next().unwrap_or(entry);
+        match &self.dev_extension {
+            Some(extension) if !name.contains('.') => Cow::Owned(format!("{entry}{extension}")),
+            _ => Cow::Borrowed(entry),
+        }
+    }
+
+    /// Returns the entrypoints, which cannot be found beneath `dev_root`.
+    ///
+    /// If `dev_root` is unset or not an accessible directory, no entrypoint
+    /// is considered missing.
+    fn missing_dev_entrypoints<'b>(&self, entrypoints: &[&'b str]) -> Vec<&'b str> {
+        let Some(root) = self.dev_root.as_deref().map(std::path::Path::new) else {
+            return vec![];
+        };
+
+        if !root.is_dir() {
+            return vec![];
+        }
+
+        entrypoints
+            .iter()
+            .filter(|entrypoint| !root.join(entrypoint).exists())
+            .copied()
+            .collect()
+    }
+}
+
+/// Options overriding how a stylesheet is linked, set per stylesheet using
+/// `ViteOptions::stylesheet_overrides`.
+///
+/// # Examples
+///
+/// ```
+/// use in_vite::ViteStylesheetOptions;
+///
+/// let dark = ViteStylesheetOptions::default()
+///     .rel("alternate stylesheet")
+///     .title("Dark");
+/// ```
+#[derive(Default, Debug, Clone)]
+pub struct ViteStylesheetOptions {
+    pub(crate) rel: Option<String>,
+    pub(crate) title: Option<String>,
+}
+
+impl ViteStylesheetOptions {
+    /// Sets the `rel` attribute, e.g. `alternate stylesheet`, in place of
+    /// `stylesheet`.
+    pub fn rel<S: Into<String>>(mut self, rel: S) -> Self {
+        self.rel = Some(rel.into());
+        self
+    }
+
+    /// Sets the `title` attribute, naming the set of stylesheets it belongs
+    /// to.
+    pub fn title<S: Into<String>>(mut self, title: S) -> Self {
+        self.title = Some(title.into());
+        self
+    }
+}
+
+/// Options `ViteReactRefresh` is constructed with, mirroring `ViteOptions`.
+#[derive(Debug, Clone)]
+pub struct ViteReactRefreshOptions {
+    pub(crate) host: String,
+    pub(crate) mode: ViteMode,
+    pub(crate) base: Option<String>,
+    pub(crate) nonce: Option<String>,
+    pub(crate) preamble: Option<String>,
+}
+
+impl Default for ViteReactRefreshOptions {
+    fn default() -> Self {
+        ViteReactRefreshOptions {
+            host: "http://localhost:5173".to_string(),
+            mode: ViteMode::default(),
+            base: None,
+            nonce: None,
+            preamble: None,
+        }
+    }
+}
+
+impl ViteReactRefreshOptions {
+    /// Sets the host, from which the refresh runtime should be loaded.
+    pub fn host(mut self, host: &str) -> Self {
+        self.host = host.to_string();
+        self
+    }
+
+    /// Sets the mode; the preamble is only emitted in development.
+    pub fn mode(mut self, mode: ViteMode) -> Self {
+        self.mode = mode;
+        self
+    }
+
+    /// Sets the public base path Vite serves from, e.g. `/app/`.
+    pub fn base<S: Into<String>>(mut self, base: S) -> Self {
+        self.base = Some(base.into());
+        self
+    }
+
+    /// Sets the CSP nonce attached to the inline preamble script.
+    pub fn nonce<S: Into<String>>(mut self, nonce: S) -> Self {
+        self.nonce = Some(nonce.into());
+        self
+    }
+
+    /// Sets the code run after `RefreshRuntime` is imported in place of
+    /// `REACT_REFRESH_PREAMBLE`.
+    pub fn preamble<S: Into<String>>(mut self, preamble: S) -> Self {
+        self.preamble = Some(preamble.into());
+        self
+    }
+}
+
+#[derive(Debug)]
+pub struct ViteReactRefresh {
+    host: String,
+    mode: ViteMode,
+    base: Option<String>,
+    nonce: Option<String>,
+    preamble: Option<String>,
+}
+
+impl ViteReactRefresh {
+    pub fn new<S: AsRef<str>>(host: S, mode: &ViteMode) -> Self {
+        Self {
+            host: host.as_ref().to_owned(),
+            mode: mode.to_owned(),
+            base: None,
+            nonce: None,
+            preamble: None,
+        }
+    }
+
+    pub fn with_options(opts: ViteReactRefreshOptions) -> Self {
+        Self {
+            host: opts.host,
+            mode: opts.mode,
+            base: opts.base,
+            nonce: opts.nonce,
+            preamble: opts.preamble,
+        }
+    }
+
+    /// Sets the public base path Vite serves from, e.g. `/app/`.
+    pub fn base<S: AsRef<str>>(mut self, base: S) -> Self {
+        self.base = Some(base.as_ref().to_owned());
+        self
+    }
+
+    /// Sets the CSP nonce attached to the inline preamble script.
+    pub fn nonce<S: AsRef<str>>(mut self, nonce: S) -> Self {
+        self.nonce = Some(nonce.as_ref().to_owned());
+        self
+    }
+
+    /// Sets the code run after `RefreshRuntime` is imported in place of
+    /// `REACT_REFRESH_PREAMBLE`, e.g. to customize `$RefreshReg$` and
+    /// `$RefreshSig$`.
+    pub fn preamble<S: AsRef<str>>(mut self, preamble: S) -> Self {
+        self.preamble = Some(preamble.as_ref().to_owned());
+        self
+    }
+
+    pub fn react_refresh(&self) -> String {
+        if self.mode == ViteMode::Development {
+            let nonce = match &self.nonce {
+                Some(nonce) => format!(r#" nonce="{nonce}""#),
+                None => "".to_string(),
+            };
+
+            format!(r#"<script type="module"{nonce}>{}</script>"#, self.script())
+        } else {
+            "".to_string()
+        }
+    }
+
+    /// Returns the CSP source, i.e. `'sha256-...'`, allowlisting the inline
+    /// preamble script by its hash, as an alternative to a nonce. Requires the
+    /// feature `csp`.
+    ///
+    /// In production there is no preamble, hence `None` is returned.
+    #[cfg(feature = "csp")]
+    pub fn csp_hash(&self) -> Option<String> {
+        use base64::Engine;
+        use sha2::{Digest, Sha256};
+
+        if self.mode != ViteMode::Development {
+            return None;
+        }
+
+        let digest = Sha256::digest(self.script().as_bytes());
+        let hash = base64::engine::general_purpose::STANDARD.encode(digest);
+        Some(format!("'sha256-{hash}'"))
+    }
+
+    /// Returns the contents of the inline preamble script.
+    fn script(&self) -> String {
+        let host = &self.host;
+        let base = normalize_base(self.base.as_deref().unwrap_or("/"));
+        let preamble = self.preamble.as_deref().unwrap_or(REACT_REFRESH_PREAMBLE);
+
+        format!(
+            r#"
+import RefreshRuntime from "{host}{base}@react-refresh"
+{preamble}
+"#
+        )
+    }
+}
+
+/// Returns the scheme, if any, and the authority of an absolute or
+/// protocol-relative URL, or `None` for paths.
+fn origin(url: &str) -> Option<(Option<&str>, &str)> {
+    let (scheme, rest) = match url.split_once("://") {
+        Some((scheme, rest)) => (Some(scheme), rest),
+        None => (None, url.strip_prefix("//")?),
+    };
+
+    let authority = rest.split('/').next().unwrap_or(rest);
+    Some((scheme, authority))
+}
+
+/// Returns the socket address of the development server at `host`, falling
+/// back to the default port of its scheme.
+#[cfg(feature = "tokio")]
+fn dev_server_address(host: &str) -> String {
+    let (scheme, authority) = origin(host).unwrap_or((None, host));
+    if authority
+        .rsplit_once(':')
+        .is_some_and(|(_, port)| !port.is_empty() && port.bytes().all(|byte| byte.is_ascii_digit()))
+    {
+        return authority.to_string();
+    }
+
+    match scheme {
+        Some(scheme) if scheme.eq_ignore_ascii_case("https") => format!("{authority}:443"),
+        _ => format!("{authority}:80"),
+    }
+}
+
+/// Holds the HTML rendered in production along with the generation of the
+/// manifest it was rendered from, which is bumped on invalidation.
+#[derive(Debug, Default)]
+struct RenderedCache {
+    generation: u64,
+    html: HashMap<String, String>,
+}
+
+/// Tracks whether anything was written into the wrapped writer.
+struct Tracked<'w, W> {
+    w: &'w mut W,
+    written: bool,
+}
+
+impl<W: Write> Write for Tracked<'_, W> {
+    fn write_str(&mut self, s: &str) -> std::fmt::Result {
+        self.written |= !s.is_empty();
+        self.w.write_str(s)
+    }
+}
+
+/// Returns whether URLs prefixed with `base` are served from an origin other
+/// than that of `page`.
+pub(crate) fn is_cross_origin(base: &str, page: Option<&str>) -> bool {
+    let Some((scheme, authority)) = origin(base) else {
+        return false;
+    };
+
+    let Some((page_scheme, page_authority)) = page.and_then(origin) else {
+        return true;
+    };
+
+    let schemes_differ = matches!(
+        (scheme, page_scheme),
+        (Some(a), Some(b)) if !a.eq_ignore_ascii_case(b)
+    );
+
+    schemes_differ || !authority.eq_ignore_ascii_case(page_authority)
+}
+
+/// Returns whether `name` is a valid HTML attribute name, i.e. non-empty and
+/// free of whitespace, control characters, quotes, `<`, `>`, `/` and `=`.
+fn is_attribute_name(name: &str) -> bool {
+    !name.is_empty()
+        && !name.chars().any(|c| {
+            c.is_whitespace() || c.is_control() || matches!(c, '"' | '\'' | '<' | '>' | '/' | '=')
+        })
+}
+
+/// Joins `path` onto the directory of the executable at `exe`, falling back to
+/// `path` itself if the executable's location is unavailable.
+fn exe_relative_path(exe: std::io::Result<std::path::PathBuf>, path: &str) -> String {
+    let dir = match exe {
+        Ok(exe) => exe.parent().map(std::path::Path::to_path_buf),
+        Err(err) => {
+            tracing::warn!("failed to locate the current executable: {err}");
+            None
+        }
+    };
+
+    match dir {
+        Some(dir) => dir.join(path).to_string_lossy().into_owned(),
+        None => path.to_string(),
+    }
+}
+
+/// Normalizes a base path to begin and end with a slash. URL bases, e.g.
+/// `https://cdn.example.com/assets`, only receive a trailing slash.
+fn normalize_base(base: &str) -> String {
+    if base.contains("://") || base.starts_with("//") {
+        return format!("{}/", base.trim_end_matches('/'));
+    }
+
+    let base = base.trim_matches('/');
+    if base.is_empty() {
+        "/".to_string()
+    } else {
+        format!("/{base}/")
+    }
+}
+
+/// The tags rendered by `Vite::render`, split into those belonging into
+/// `<head>` and `<body>`. Displays all tags as rendered by `Vite::to_html`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ViteTags {
+    html: String,
+    head: String,
+    body: String,
+}
+
+impl ViteTags {
+    /// Returns the tags belonging into `<head>`, i.e. stylesheets and
+    /// preloads.
+    pub fn head(&self) -> &str {
+        &self.head
+    }
+
+    /// Returns the tags belonging into `<body>`, i.e. scripts.
+    pub fn body(&self) -> &str {
+        &self.body
+    }
+}
+
+impl std::fmt::Display for ViteTags {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(&self.html)
+    }
+}
+
+#[derive(Debug)]
+pub struct ViteDevBanner {
+    host: String,
+    mode: ViteMode,
+    base: Option<String>,
+}
+
+impl ViteDevBanner {
+    pub fn new<S: AsRef<str>>(host: S, mode: &ViteMode) -> Self {
+        Self {
+            host: host.as_ref().to_owned(),
+            mode: mode.to_owned(),
+            base: None,
+        }
+    }
+
+    /// Sets the public base path Vite serves from, e.g. `/app/`.
+    pub fn base<S: AsRef<str>>(mut self, base: S) -> Self {
+        self.base = Some(base.as_ref().to_owned());
+        self
+    }
+
+    pub fn dev_banner_html(&self) -> String {
+        if self.mode == ViteMode::Development {
+            let host = &self.host;
+            let base = self.base.as_deref().unwrap_or("/");
+
+            format!(
+                r#"<div id="in-vite-dev-banner" hidden style="position:fixed;bottom:0;left:0;right:0;z-index:2147483647;padding:8px 12px;background:#b91c1c;color:#fff;font:14px sans-serif">The Vite development server at <code>{host}</code> is not reachable. Start it with <code>npm run dev</code>.</div>
+<script type="module">
+import("{host}{base}@vite/client").catch(() => document.getElementById("in-vite-dev-banner").hidden = false)
+</script>"#
+            )
+        } else {
+            "".to_string()
+        }
+    }
+}
+
+/// Derives the URL of the websocket the dev server pushes hot module
+/// replacement updates through, e.g. to display the connection status.
+#[derive(Debug)]
+pub struct ViteHmrUrl {
+    host: String,
+    mode: ViteMode,
+    base: Option<String>,
+}
+
+impl ViteHmrUrl {
+    pub fn new<S: AsRef<str>>(host: S, mode: &ViteMode) -> Self {
+        Self {
+            host: host.as_ref().to_owned(),
+            mode: mode.to_owned(),
+            base: None,
+        }
+    }
+
+    /// Sets the public base path Vite serves from, e.g. `/app/`.
+    pub fn base<S: AsRef<str>>(mut self, base: S) -> Self {
+        self.base = Some(base.as_ref().to_owned());
+        self
+    }
+
+    /// Returns the websocket URL, i.e. `ws://` or `wss://` for a secure dev
+    /// server, in development and an empty string in production.
+    pub fn hmr_url(&self) -> String {
+        if self.mode != ViteMode::Development {
+            return String::new();
+        }
+
+        let host = self.host.trim_end_matches('/');
+        let base = self.base.as_deref().unwrap_or("/");
+        if let Some(authority) = host.strip_prefix("https://") {
+            format!("wss://{authority}{base}")
+        } else {
+            let authority = host.strip_prefix("http://").unwrap_or(host);
+            format!("ws://{authority}{base}")
+        }
+    }
+}
+
+/// Renders a `<base>` tag pointing at the base resources are served from,
+/// e.g. for apps served under a sub-path using relative URLs.
+#[derive(Debug)]
+pub struct ViteBaseTag {
+    base: String,
+}
+
+impl ViteBaseTag {
+    pub fn new<S: AsRef<str>>(base: S) -> Self {
+        Self {
+            base: base.as_ref().to_owned(),
+        }
+    }
+
+    /// Returns the `<base>` tag, or an empty string if the base is unset,
+    /// i.e. empty or `/`.
+    pub fn base_tag(&self) -> String {
+        if self.base.is_empty() || self.base == "/" {
+            return String::new();
+        }
+
+        format!(r#"<base href="{}">"#, self.base)
+    }
+}
+
+/// Renders the prefetches of the given entrypoints using `Vite::prefetch`,
+/// e.g. to bind them as a template function besides `Vite` itself.
+#[derive(Debug, Clone)]
+pub struct VitePrefetch {
+    vite: Vite,
+}
+
+impl VitePrefetch {
+    /// Creates the prefetcher from a clone of `vite`, sharing its cached
+    /// manifest.
+    pub fn new(vite: &Vite) -> Self {
+        Self { vite: vite.clone() }
+    }
+
+    /// Returns the `<link rel="prefetch">` tags of the given entrypoints.
+    pub fn prefetch(&self, entrypoints: Vec<&str>) -> Result<String, Error> {
+        self.vite.prefetch(entrypoints)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::{
+        exe_relative_path, AssetKind, Vite, ViteCrossOrigin, ViteCssMode, ViteMode, ViteOptions,
+        ViteReactRefresh, ViteReactRefreshOptions, ViteStylesheetOptions, MODULEPRELOAD_POLYFILL,
+        REACT_REFRESH_PREAMBLE,
+    };
+    use crate::manifest::ResolutionStatus;
+    use crate::resource::{render_tags, TagOptions};
+    use std::collections::HashMap;
+    use std::sync::PoisonError;
+    use tracing_test::traced_test;
+
+    const SSR_MANIFEST: &str = include_str!("../test/ssr_manifest.json");
+    const FONTS_MANIFEST: &str = include_str!("../test/fonts_manifest.json");
+    const ASSETS_MANIFEST: &str = include_str!("../test/assets_manifest.json");
+    const SAMPLE_MANIFEST: &str = include_str!("../test/sample_manifest.json");
+
+    #[test]
+    fn dev_banner_is_emitted_in_development() {
+        let vite = Vite::with_options(ViteOptions::default().mode(ViteMode::Development));
+        let banner = vite.dev_banner_html();
+
+        assert!(!banner.is_empty());
+        assert!(banner.contains(r#"import("http://localhost:5173/@vite/client")"#));
+    }
+
+    #[test]
+    fn dev_banner_is_empty_in_production() {
+        let vite = Vite::with_options(ViteOptions::default().mode(ViteMode::Production));
+
+        assert_eq!(vite.dev_banner_html(), "");
+    }
+
+    #[test]
+    #[traced_test]
+    fn warns_about_missing_dev_entrypoint() {
+        let opts = ViteOptions::default()
+            .mode(ViteMode::Development)
+            .dev_root("src");
+
+        let vite = Vite::with_options(opts);
+        let html = vite.to_html(vec!["lib.rs", "missing.js"]).unwrap();
+
+        assert!(html.contains("http://localhost:5173/missing.js"));
+        assert!(logs_contain("'missing.js' does not exist"));
+        assert!(!logs_contain("'lib.rs' does not exist"));
+    }
+
+    #[test]
+    fn react_refresh_respects_base_and_nonce() {
+        let refresh = ViteReactRefresh::new("http://localhost:5173", &ViteMode::Development)
+            .base("/app/")
+            .nonce("r4nd0m");
+
+        let html = refresh.react_refresh();
+
+        assert!(html.starts_with(r#"<script type="module" nonce="r4nd0m">"#));
+        assert!(html
+            .contains(r#"import RefreshRuntime from "http://localhost:5173/app/@react-refresh""#));
+    }
+
+    #[test]
+    fn react_refresh_accepts_custom_preamble() {
+        let preamble = REACT_REFRESH_PREAMBLE.replace(
+            "window.$RefreshReg$ = () => {}",
+            "window.$RefreshReg$ = (type, id) => registerBoundary(type, id)",
+        );
+
+        let refresh = ViteReactRefresh::new("http://localhost:5173", &ViteMode::Development)
+            .preamble(preamble);
+
+        let expected = r#"<script type="module">
+import RefreshRuntime from "http://localhost:5173/@react-refresh"
+RefreshRuntime.injectIntoGlobalHook(window)
+window.$RefreshReg$ = (type, id) => registerBoundary(type, id)
+window.$RefreshSig$ = () => (type) => type
+window.__vite_plugin_react_preamble_installed__ = true
+</script>"#;
+
+        assert_eq!(refresh.react_refresh(), expected);
+    }
+
+    #[test]
+    fn can_construct_react_refresh_with_options() {
+        let opts = ViteReactRefreshOptions::default()
+            .host("http://localhost:3000")
+            .mode(ViteMode::Development)
+            .base("/app/")
+            .nonce("r4nd0m");
+
+        let refresh = ViteReactRefresh::with_options(opts);
+        let expected = format!(
+            r#"<script type="module" nonce="r4nd0m">
+import RefreshRuntime from "http://localhost:3000/app/@react-refresh"
+{REACT_REFRESH_PREAMBLE}
+</script>"#
+        );
+
+        assert_eq!(refresh.react_refresh(), expected);
+    }
+
+    #[test]
+    fn react_refresh_options_respect_mode() {
+        let opts = ViteReactRefreshOptions::default().mode(ViteMode::Production);
+
+        assert_eq!(ViteReactRefresh::with_options(opts).react_refresh(), "");
+    }
+
+    #[cfg(feature = "csp")]
+    #[test]
+    fn react_refresh_csp_hash_matches_preamble() {
+        use base64::Engine;
+        use sha2::{Digest, Sha256};
+
+        let refresh = ViteReactRefresh::new("http://localhost:5173", &ViteMode::Development);
+        let html = refresh.react_refresh();
+        let script = html
+            .strip_prefix(r#"<script type="module">"#)
+            .and_then(|html| html.strip_suffix("</script>"))
+            .unwrap();
+
+        let hash = base64::engine::general_purpose::STANDARD.encode(Sha256::digest(script));
+
+        assert_eq!(refresh.csp_hash(), Some(format!("'sha256-{hash}'")));
+        assert_eq!(
+            ViteReactRefresh::new("http://localhost:5173", &ViteMode::Production).csp_hash(),
+            None
+        );
+    }
+
+    #[cfg(feature = "glob")]
+    #[test]
+    fn can_resolve_entrypoints_by_glob() {
+        let opts = ViteOptions::default()
+            .mode(ViteMode::Production)
+            .source(Some(include_str!("../test/glob_manifest.json")));
+
+        let vite = Vite::with_options(opts);
+        let html = vite.to_html_glob(&["admin/*.js", "admin/users.*"]).unwrap();
+        let expected = r#"<link rel="stylesheet" href="/assets/users-D9rT0wQe.css" />
+<script type="module" src="/assets/posts-Cx1a9KdE.js"></script>
+<script type="module" src="/assets/users-B4mZq2Lp.js"></script>"#;
+
+        assert_eq!(html, expected);
+    }
+
+    #[cfg(feature = "glob")]
+    #[test]
+    fn includes_shared_resources_of_globbed_entrypoints_once() {
+        let vite = Vite::for_testing(SAMPLE_MANIFEST, ViteMode::Production);
+        let html = vite.to_html_glob(&["views/*.js"]).unwrap();
+        let expected = r#"<link rel="stylesheet" href="/assets/shared-ChJ_j-JJ.css" />
+<link rel="stylesheet" href="/assets/foo-5UjPuW-k.css" />
+<script type="module" src="/assets/bar-gkvgaI9m.js"></script>
+<script type="module" src="/assets/foo-BRBmoGS9.js"></script>
+<link rel="modulepreload" href="/assets/shared-B7PI925R.js" />"#;
+
+        assert_eq!(html, expected);
+        assert_eq!(html.matches("shared-ChJ_j-JJ.css").count(), 1);
+        assert_eq!(html.matches("shared-B7PI925R.js").count(), 1);
+    }
+
+    #[test]
+    fn renders_umd_entry_as_classic_script() {
+        let vite = Vite::for_testing(
+            include_str!("../test/umd_manifest.json"),
+            ViteMode::Production,
+        );
+        let html = vite.to_html(vec!["lib/widget.js"]).unwrap();
+
+        assert_eq!(
+            html,
+            r#"<script defer src="/assets/widget-Dk3m8PqA.umd.js"></script>"#
+        );
+    }
+
+    #[test]
+    fn can_override_host_per_render() {
+        let vite = Vite::for_testing(SAMPLE_MANIFEST, ViteMode::Development);
+        let first = vite
+            .to_html_with_host(vec!["app.js"], "http://tenant-a.localhost:5173")
+            .unwrap();
+        let second = vite
+            .to_html_with_host(vec!["app.js"], "http://tenant-b.localhost:5173")
+            .unwrap();
+
+        assert!(first.contains(r#"src="http://tenant-a.localhost:5173/@vite/client""#));
+        assert!(first.contains(r#"src="http://tenant-a.localhost:5173/app.js""#));
+        assert!(second.contains(r#"src="http://tenant-b.localhost:5173/@vite/client""#));
+        assert!(second.contains(r#"src="http://tenant-b.localhost:5173/app.js""#));
+    }
+
+    #[test]
+    fn can_override_host_per_render_with_base() {
+        let opts = ViteOptions::default()
+            .mode(ViteMode::Production)
+            .base("/static/")
+            .source(Some(SAMPLE_MANIFEST));
+
+        let vite = Vite::with_options(opts);
+        let first = vite
+            .to_html_with_host(vec!["views/foo.js"], "https://a.example.com")
+            .unwrap();
+        let second = vite
+            .to_html_with_host(vec!["views/foo.js"], "https://b.example.com")
+            .unwrap();
+
+        assert!(first.contains(r#"src="https://a.example.com/static/assets/foo-BRBmoGS9.js""#));
+        assert!(second.contains(r#"src="https://b.example.com/static/assets/foo-BRBmoGS9.js""#));
+        assert!(vite
+            .to_html(vec!["views/foo.js"])
+            .unwrap()
+            .contains(r#"src="/static/assets/foo-BRBmoGS9.js""#));
+    }
+
+    #[cfg(feature = "yaml")]
+    #[test]
+    fn can_load_yaml_manifest() {
+        let yaml = ViteOptions::default()
+            .mode(ViteMode::Production)
+            .manifest_format(super::ManifestFormat::Yaml)
+            .source(Some(include_str!("../test/sample_manifest.yaml")));
+        let json = ViteOptions::default()
+            .mode(ViteMode::Production)
+            .source(Some(SAMPLE_MANIFEST));
+
+        let yaml = Vite::with_options(yaml)
+            .to_html(vec!["views/foo.js"])
+            .unwrap();
+        let json = Vite::with_options(json)
+            .to_html(vec!["views/foo.js"])
+            .unwrap();
+
+        assert_eq!(yaml, json);
+    }
+
+    #[cfg(feature = "toml")]
+    #[test]
+    fn can_load_toml_manifest() {
+        let toml = ViteOptions::default()
+            .mode(ViteMode::Production)
+            .manifest_format(super::ManifestFormat::Toml)
+            .manifest_path("test/sample_manifest.toml");
+        let json = ViteOptions::default()
+            .mode(ViteMode::Production)
+            .source(Some(SAMPLE_MANIFEST));
+
+        let toml = Vite::with_options(toml)
+            .to_html(vec!["views/foo.js"])
+            .unwrap();
+        let json = Vite::with_options(json)
+            .to_html(vec!["views/foo.js"])
+            .unwrap();
+
+        assert_eq!(toml, json);
+    }
+
+    #[test]
+    fn can_include_dynamic_import_css() {
+        let opts = ViteOptions::default()
+            .mode(ViteMode::Production)
+            .dynamic_import_css(true)
+            .source(Some(include_str!("../test/dynamic_manifest.json")));
+
+        let vite = Vite::with_options(opts);
+        let html = vite.to_html(vec!["views/app.js"]).unwrap();
+        let expected = r#"<link rel="stylesheet" href="/assets/shared-ChJ_j-JJ.css" />
+<link rel="stylesheet" href="/assets/chart-Hs8PwZ1v.css" />
+<link rel="stylesheet" href="/assets/reports-Lw4cV9Xe.css" />
+<script type="module" src="/assets/app-Rz5kM1Nq.js"></script>
+<link rel="modulepreload" href="/assets/shared-B7PI925R.js" />"#;
+
+        assert_eq!(html, expected);
+    }
+
+    #[test]
+    fn excludes_dynamic_import_css_by_default() {
+        let vite = Vite::for_testing(
+            include_str!("../test/dynamic_manifest.json"),
+            ViteMode::Production,
+        );
+        let html = vite.to_html(vec!["views/app.js"]).unwrap();
+        let expected = r#"<link rel="stylesheet" href="/assets/shared-ChJ_j-JJ.css" />
+<script type="module" src="/assets/app-Rz5kM1Nq.js"></script>
+<link rel="modulepreload" href="/assets/shared-B7PI925R.js" />"#;
+
+        assert_eq!(html, expected);
+    }
+
+    #[test]
+    fn reloads_manifest_after_invalidation() {
+        let path =
+            std::env::temp_dir().join(format!("in-vite-{}-manifest.json", std::process::id()));
+        std::fs::write(&path, SAMPLE_MANIFEST).unwrap();
+
+        let opts = ViteOptions::default()
+            .mode(ViteMode::Production)
+            .manifest_path(path.to_string_lossy());
+
+        let vite = Vite::with_options(opts);
+        let before = vite.to_html(vec!["views/foo.js"]).unwrap();
+
+        std::fs::write(
+            &path,
+            SAMPLE_MANIFEST.replace("foo-BRBmoGS9", "foo-Xk29sLq0"),
+        )
+        .unwrap();
+        let cached = vite.to_html(vec!["views/foo.js"]).unwrap();
+
+        vite.invalidate_manifest();
+        let after = vite.to_html(vec!["views/foo.js"]).unwrap();
+        std::fs::remove_file(&path).unwrap();
+
+        assert_eq!(before, cached);
+        assert!(before.contains("/assets/foo-BRBmoGS9.js"));
+        assert!(after.contains("/assets/foo-Xk29sLq0.js"));
+    }
+
+    #[test]
+    fn normalizes_backslashes_in_urls() {
+        let opts = ViteOptions::default()
+            .mode(ViteMode::Production)
+            .source(Some(include_str!("../test/windows_manifest.json")));
+
+        let vite = Vite::with_options(opts);
+        let html = vite.to_html(vec!["views/foo.js"]).unwrap();
+        let expected = r#"<link rel="stylesheet" href="/assets/foo-5UjPuW-k.css" />
+<script type="module" src="/assets/foo-BRBmoGS9.js"></script>"#;
+
+        assert_eq!(html, expected);
+    }
+
+    #[test]
+    fn can_write_html_into_buffer() {
+        let opts = ViteOptions::default()
+            .mode(ViteMode::Production)
+            .source(Some(SAMPLE_MANIFEST));
+
+        let vite = Vite::with_options(opts);
+        let mut html = String::from("<head>");
+        vite.write_html(&mut html, vec!["views/foo.js"]).unwrap();
+
+        let expected = format!("<head>{}", vite.to_html(vec!["views/foo.js"]).unwrap());
+        assert_eq!(html, expected);
+    }
+
+    #[test]
+    fn can_force_non_entry_chunk() {
+        let opts = ViteOptions::default()
+            .mode(ViteMode::Production)
+            .source(Some(SAMPLE_MANIFEST));
+        let forced = Vite::with_options(opts.allow_non_entry(true));
+
+        assert_eq!(
+            forced.to_html(vec!["baz.js"]).unwrap(),
+            r#"<script type="module" src="/assets/baz-B2H3sXNv.js"></script>"#
+        );
+
+        let opts = ViteOptions::default()
+            .mode(ViteMode::Production)
+            .source(Some(SAMPLE_MANIFEST));
+        let default = Vite::with_options(opts);
+
+        assert_eq!(default.to_html(vec!["baz.js"]).unwrap(), "");
+    }
+
+    #[test]
+    fn can_check_entry_for_css() {
+        let opts = ViteOptions::default()
+            .mode(ViteMode::Production)
+            .source(Some(include_str!("../test/glob_manifest.json")));
+
+        let vite = Vite::with_options(opts);
+
+        assert!(vite.entry_has_css("admin/users.js").unwrap());
+        assert!(!vite.entry_has_css("admin/posts.js").unwrap());
+    }
+
+    #[test]
+    fn sample_entry_has_css() {
+        let opts = ViteOptions::default()
+            .mode(ViteMode::Production)
+            .source(Some(SAMPLE_MANIFEST));
+
+        let vite = Vite::with_options(opts);
+
+        assert!(vite.entry_has_css("views/foo.js").unwrap());
+    }
+
+    #[test]
+    fn defers_classic_scripts_only() {
+        let opts = ViteOptions::default()
+            .mode(ViteMode::Production)
+            .source(Some(include_str!("../test/umd_manifest.json")));
+
+        let vite = Vite::with_options(opts);
+        let html = vite.to_html(vec!["lib/legacy.js", "views/foo.js"]).unwrap();
+        let expected = r#"<script type="module" src="/assets/foo-BRBmoGS9.js"></script>
+<script defer src="/assets/legacy-C7vY2nLs.js"></script>"#;
+
+        assert_eq!(html, expected);
+
+        let opts = ViteOptions::default()
+            .mode(ViteMode::Production)
+            .defer_classic(false)
+            .source(Some(include_str!("../test/umd_manifest.json")));
+
+        let vite = Vite::with_options(opts);
+        let html = vite.to_html(vec!["lib/legacy.js"]).unwrap();
+
+        assert_eq!(
+            html,
+            r#"<script src="/assets/legacy-C7vY2nLs.js"></script>"#
+        );
+    }
+
+    #[test]
+    fn can_read_host_from_env() {
+        let _lock = ENV_LOCK.lock().unwrap_or_else(PoisonError::into_inner);
+        std::env::set_var("VITE_DEV_SERVER_URL", "http://localhost:3000/");
+        let opts = ViteOptions::default()
+            .mode(ViteMode::Development)
+            .host_from_env("VITE_DEV_SERVER_URL")
+            .host_from_env("IN_VITE_UNSET_DEV_SERVER_URL");
+        std::env::remove_var("VITE_DEV_SERVER_URL");
+
+        let vite = Vite::with_options(opts);
+        let html = vite.to_html(vec!["app.js"]).unwrap();
+
+        assert!(html.contains(r#"src="http://localhost:3000/@vite/client""#));
+        assert!(html.contains(r#"src="http://localhost:3000/app.js""#));
+    }
+
+    #[test]
+    fn can_locate_wrapped_manifest_by_pointer() {
+        let opts = ViteOptions::default()
+            .mode(ViteMode::Production)
+            .manifest_pointer(Some("/manifest"))
+            .source(Some(include_str!("../test/wrapped_manifest.json")));
+
+        let wrapped = Vite::with_options(opts)
+            .to_html(vec!["views/foo.js"])
+            .unwrap();
+
+        let opts = ViteOptions::default()
+            .mode(ViteMode::Production)
+            .source(Some(SAMPLE_MANIFEST));
+
+        let plain = Vite::with_options(opts)
+            .to_html(vec!["views/foo.js"])
+            .unwrap();
+
+        assert_eq!(wrapped, plain);
+    }
+
+    #[test]
+    fn fails_on_unresolvable_manifest_pointer() {
+        let opts = ViteOptions::default()
+            .mode(ViteMode::Production)
+            .manifest_pointer(Some("/chunks"))
+            .source(Some(include_str!("../test/wrapped_manifest.json")));
+
+        let result = Vite::with_options(opts).to_html(vec!["views/foo.js"]);
+
+        assert!(matches!(result, Err(crate::Error::ManifestPointer(_))));
+    }
+
+    #[test]
+    fn can_combine_development_entries() {
+        let opts = ViteOptions::default()
+            .mode(ViteMode::Development)
+            .combine_entries(true);
+
+        let vite = Vite::with_options(opts);
+        let html = vite.to_html(vec!["app.js", "admin.js"]).unwrap();
+        let expected = concat!(
+            r#"<script type="module">"#,
+            r#"import "http://localhost:5173/@vite/client";"#,
+            r#"import "http://localhost:5173/app.js";"#,
+            r#"import "http://localhost:5173/admin.js";"#,
+            "</script>"
+        );
+
+        assert_eq!(html, expected);
+    }
+
+    #[test]
+    fn can_resolve_worker_url() {
+        let opts = ViteOptions::default()
+            .mode(ViteMode::Production)
+            .base("/app/")
+            .source(Some(include_str!("../test/worker_manifest.json")));
+
+        let vite = Vite::with_options(opts);
+
+        assert_eq!(
+            vite.worker_url("src/workers/search.js").unwrap().as_deref(),
+            Some("/app/assets/search-Bq8vN2xL.js")
+        );
+        assert_eq!(vite.worker_url("src/workers/missing.js").unwrap(), None);
+    }
+
+    #[test]
+    fn serves_worker_from_dev_server() {
+        let vite = Vite::with_options(ViteOptions::default().mode(ViteMode::Development));
+
+        assert_eq!(
+            vite.worker_url("src/workers/search.js").unwrap().as_deref(),
+            Some("http://localhost:5173/src/workers/search.js")
+        );
+    }
+
+    #[test]
+    fn can_disable_preloads() {
+        let opts = ViteOptions::default()
+            .mode(ViteMode::Production)
+            .emit_preloads(false)
+            .source(Some(SAMPLE_MANIFEST));
+
+        let vite = Vite::with_options(opts);
+        let html = vite.to_html(vec!["views/foo.js"]).unwrap();
+        let expected = r#"<link rel="stylesheet" href="/assets/shared-ChJ_j-JJ.css" />
+<link rel="stylesheet" href="/assets/foo-5UjPuW-k.css" />
+<script type="module" src="/assets/foo-BRBmoGS9.js"></script>"#;
+
+        assert_eq!(html, expected);
+    }
+
+    #[test]
+    fn can_match_on_resolved_asset_kinds() {
+        let opts = ViteOptions::default()
+            .mode(ViteMode::Production)
+            .source(Some(SAMPLE_MANIFEST));
+
+        let vite = Vite::with_options(opts);
+        let assets = vite.resolve(vec!["views/foo.js"]).unwrap();
+
+        let mut stylesheets = Vec::new();
+        let mut modules = Vec::new();
+        let mut preloads = Vec::new();
+        for asset in &assets {
+            match asset.kind {
+                AssetKind::Stylesheet => stylesheets.push(asset.url.as_str()),
+                AssetKind::Module => modules.push(asset.url.as_str()),
+                AssetKind::PreloadModule => preloads.push(asset.url.as_str()),
+                AssetKind::Script | AssetKind::PreloadStylesheet | AssetKind::PreloadAsset => {
+                    panic!("unexpected asset {}", asset.url)
+                }
+            }
+        }
+
+        assert_eq!(
+            stylesheets,
+            vec!["/assets/shared-ChJ_j-JJ.css", "/assets/foo-5UjPuW-k.css"]
+        );
+        assert_eq!(modules, vec!["/assets/foo-BRBmoGS9.js"]);
+        assert_eq!(preloads, vec!["/assets/shared-B7PI925R.js"]);
+    }
+
+    #[test]
+    fn falls_back_to_default_entry() {
+        let opts = ViteOptions::default()
+            .mode(ViteMode::Production)
+            .fallback_entry(Some("views/bar.js"))
+            .source(Some(SAMPLE_MANIFEST));
+
+        let vite = Vite::with_options(opts);
+        let html = vite.to_html(vec!["plugins/missing.js"]).unwrap();
+        let expected = vite.to_html(vec!["views/bar.js"]).unwrap();
+
+        assert!(!html.is_empty());
+        assert_eq!(html, expected);
+    }
+
+    #[test]
+    fn preloads_data_assets_as_fetch() {
+        let opts = ViteOptions::default()
+            .mode(ViteMode::Production)
+            .preload_assets(true)
+            .source(Some(ASSETS_MANIFEST));
+
+        let vite = Vite::with_options(opts);
+        let html = vite.to_html(vec!["views/app.js"]).unwrap();
+        let expected = r#"<script type="module" src="/assets/app-C4xq9LmZ.js"></script>
+<link rel="preload" as="fetch" crossorigin href="/assets/data-Bz8kT2Qa.json" />
+<link rel="preload" as="fetch" crossorigin href="/assets/engine-D1rPq7Xw.wasm" />"#;
+
+        assert_eq!(html, expected);
+    }
+
+    #[test]
+    fn can_render_html5_void_links() {
+        let manifest = r#"{
+            "style.css": { "file": "assets/style-Dq3vXk9B.css", "src": "style.css", "isEntry": true }
+        }"#;
+
+        let xhtml = Vite::with_options(
+            ViteOptions::default()
+                .mode(ViteMode::Production)
+                .source(Some(manifest)),
+        );
+        let html5 = Vite::with_options(
+            ViteOptions::default()
+                .mode(ViteMode::Production)
+                .xhtml(false)
+                .source(Some(manifest)),
+        );
+
+        assert_eq!(
+            xhtml.to_html(vec!["style.css"]).unwrap(),
+            r#"<link rel="stylesheet" href="/assets/style-Dq3vXk9B.css" />"#
+        );
+        assert_eq!(
+            html5.to_html(vec!["style.css"]).unwrap(),
+            r#"<link rel="stylesheet" href="/assets/style-Dq3vXk9B.css">"#
+        );
+    }
+
+    #[test]
+    fn can_preload_fonts() {
+        let opts = ViteOptions::default()
+            .mode(ViteMode::Production)
+            .source(Some(FONTS_MANIFEST));
+
+        let vite = Vite::with_options(opts);
+        let html = vite.font_preloads("views/app.js").unwrap();
+        let expected =
+            r#"<link rel="preload" as="font" crossorigin href="/assets/inter-Bq7xK2mP.woff2" />"#;
+
+        assert_eq!(html, expected);
+    }
+
+    #[test]
+    fn tags_carry_originating_entry() {
+        let opts = ViteOptions::default()
+            .mode(ViteMode::Production)
+            .tag_entry_attribute(true)
+            .source(Some(SAMPLE_MANIFEST));
+
+        let vite = Vite::with_options(opts);
+        let html = vite.to_html(vec!["views/foo.js"]).unwrap();
+        let expected = r#"<link rel="stylesheet" href="/assets/shared-ChJ_j-JJ.css" data-vite-entry="views/foo.js" />
+<link rel="stylesheet" href="/assets/foo-5UjPuW-k.css" data-vite-entry="views/foo.js" />
+<script type="module" src="/assets/foo-BRBmoGS9.js" data-vite-entry="views/foo.js"></script>
+<link rel="modulepreload" href="/assets/shared-B7PI925R.js" data-vite-entry="views/foo.js" />"#;
+
+        assert_eq!(html, expected);
+
+        let html = vite.to_html(vec!["views/bar.js", "views/foo.js"]).unwrap();
+        assert!(html.contains(
+            r#"<script type="module" src="/assets/foo-BRBmoGS9.js" data-vite-entry="views/foo.js"></script>"#
+        ));
+        assert!(html.contains(
+            r#"<link rel="stylesheet" href="/assets/shared-ChJ_j-JJ.css" data-vite-entry="views/bar.js" />"#
+        ));
+        assert!(!html.contains(
+            r#"<link rel="stylesheet" href="/assets/shared-ChJ_j-JJ.css" data-vite-entry="views/foo.js" />"#
+        ));
+        assert_eq!(html.matches("shared-ChJ_j-JJ.css").count(), 1);
+        assert_eq!(html.matches("shared-B7PI925R.js").count(), 1);
+    }
+
+    #[test]
+    fn appends_dev_extension_to_extensionless_entries() {
+        let opts = ViteOptions::default()
+            .mode(ViteMode::Development)
+            .dev_extension(Some(".ts"));
+
+        let vite = Vite::with_options(opts);
+        let html = vite.to_html(vec!["main", "admin.js"]).unwrap();
+        let expected = r#"<script type="module" src="http://localhost:5173/@vite/client"></script>
+<script type="module" src="http://localhost:5173/main.ts"></script>
+<script type="module" src="http://localhost:5173/admin.js"></script>"#;
+
+        assert_eq!(html, expected);
+    }
+
+    #[test]
+    fn can_inline_named_stylesheets() {
+        let out_dir = std::env::temp_dir().join(format!("in-vite-{}-inline", std::process::id()));
+        std::fs::create_dir_all(out_dir.join("assets")).unwrap();
+        std::fs::write(out_dir.join("assets/foo-5UjPuW-k.css"), "body{margin:0}").unwrap();
+
+        let opts = ViteOptions::default()
+            .mode(ViteMode::Production)
+            .out_dir(out_dir.to_string_lossy())
+            .inline_stylesheets(vec!["foo-5UjPuW-k.css".to_string()])
+            .source(Some(SAMPLE_MANIFEST));
+
+        let vite = Vite::with_options(opts);
+        let html = vite.to_html(vec!["views/foo.js"]).unwrap();
+        std::fs::remove_dir_all(&out_dir).unwrap();
+
+        let expected = r#"<link rel="stylesheet" href="/assets/shared-ChJ_j-JJ.css" />
+<style>body{margin:0}</style>
+<script type="module" src="/assets/foo-BRBmoGS9.js"></script>
+<link rel="modulepreload" href="/assets/shared-B7PI925R.js" />"#;
+
+        assert_eq!(html, expected);
+    }
+
+    #[test]
+    fn handles_empty_entrypoints() {
+        let development = Vite::with_options(ViteOptions::default().mode(ViteMode::Development));
+        assert_eq!(
+            development.to_html(vec![]).unwrap(),
+            r#"<script type="module" src="http://localhost:5173/@vite/client"></script>"#
+        );
+
+        let development = Vite::with_options(
+            ViteOptions::default()
+                .mode(ViteMode::Development)
+                .client_without_entries(false),
+        );
+        assert_eq!(development.to_html(vec![]).unwrap(), "");
+
+        let production = Vite::with_options(
+            ViteOptions::default()
+                .mode(ViteMode::Production)
+                .source(Some(SAMPLE_MANIFEST)),
+        );
+        assert_eq!(production.to_html(vec![]).unwrap(), "");
+    }
+
+    #[test]
+    fn applies_crossorigin_to_cross_origin_urls_only() {
+        let cdn = Vite::with_options(
+            ViteOptions::default()
+                .mode(ViteMode::Production)
+                .crossorigin(ViteCrossOrigin::Auto)
+                .base("https://cdn.example.com/")
+                .source(Some(SAMPLE_MANIFEST)),
+        );
+
+        let html = cdn
+            .to_html_with_host(vec!["views/foo.js"], "https://app.example.com")
+            .unwrap();
+        let expected = r#"<link rel="stylesheet" crossorigin href="https://cdn.example.com/assets/shared-ChJ_j-JJ.css" />
+<link rel="stylesheet" crossorigin href="https://cdn.example.com/assets/foo-5UjPuW-k.css" />
+<script type="module" crossorigin src="https://cdn.example.com/assets/foo-BRBmoGS9.js"></script>
+<link rel="modulepreload" crossorigin href="https://cdn.example.com/assets/shared-B7PI925R.js" />"#;
+        assert_eq!(html, expected);
+
+        let html = cdn
+            .to_html_with_host(vec!["views/foo.js"], "https://cdn.example.com")
+            .unwrap();
+        assert!(!html.contains("crossorigin"));
+
+        let local = Vite::with_options(
+            ViteOptions::default()
+                .mode(ViteMode::Production)
+                .crossorigin(ViteCrossOrigin::Auto)
+                .source(Some(SAMPLE_MANIFEST)),
+        );
+
+        let html = local
+            .to_html_with_host(vec!["views/foo.js"], "https://app.example.com")
+            .unwrap();
+        assert!(!html.contains("crossorigin"));
+        assert!(!local
+            .to_html(vec!["views/foo.js"])
+            .unwrap()
+            .contains("crossorigin"));
+    }
+
+    #[cfg(feature = "tokio")]
+    #[tokio::test]
+    async fn waits_for_delayed_dev_server() {
+        let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+        let address = listener.local_addr().unwrap();
+        drop(listener);
+
+        let server = tokio::spawn(async move {
+            tokio::time::sleep(std::time::Duration::from_millis(200)).await;
+            let listener = tokio::net::TcpListener::bind(address).await.unwrap();
+            let _ = listener.accept().await;
+        });
+
+        let opts = ViteOptions::default()
+            .mode(ViteMode::Development)
+            .host(&format!("http://{address}"));
+
+        let vite = Vite::with_options(opts);
+        let result = vite
+            .wait_for_dev_server(std::time::Duration::from_secs(5))
+            .await;
+
+        assert!(result.is_ok());
+        server.await.unwrap();
+    }
+
+    #[cfg(feature = "tokio")]
+    #[tokio::test]
+    async fn gives_up_waiting_for_dev_server() {
+        let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+        let address = listener.local_addr().unwrap();
+        drop(listener);
+
+        let opts = ViteOptions::default()
+            .mode(ViteMode::Development)
+            .host(&format!("http://{address}"));
+
+        let vite = Vite::with_options(opts);
+        let result = vite
+            .wait_for_dev_server(std::time::Duration::from_millis(200))
+            .await;
+
+        assert!(matches!(result, Err(crate::Error::DevServerUnavailable(_))));
+    }
+
+    #[cfg(feature = "tokio")]
+    #[tokio::test]
+    async fn checks_dev_server_availability() {
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let address = listener.local_addr().unwrap();
+
+        let opts = ViteOptions::default()
+            .mode(ViteMode::Development)
+            .host(&format!("http://{address}"))
+            .dev_check_timeout(std::time::Duration::from_millis(100))
+            .dev_check_retries(2);
+
+        let vite = Vite::with_options(opts);
+        assert!(vite.dev_server_available().await);
+
+        drop(listener);
+        let started = std::time::Instant::now();
+        assert!(!vite.dev_server_available().await);
+        assert!(started.elapsed() < std::time::Duration::from_secs(1));
+    }
+
+    #[test]
+    fn can_preload_rendered_modules() {
+        let opts = ViteOptions::default()
+            .mode(ViteMode::Production)
+            .ssr_manifest_source(Some(SSR_MANIFEST));
+
+        let vite = Vite::with_options(opts);
+        let html = vite
+            .preload_for_modules(&[
+                "src/components/Header.vue",
+                "src/App.vue",
+                "src/Unknown.vue",
+            ])
+            .unwrap();
+        let expected = r#"<link rel="stylesheet" href="/assets/App-Dn5kQ1Wz.css" />
+<link rel="modulepreload" href="/assets/App-Cq2vT8Lm.js" />
+<link rel="modulepreload" href="/assets/Header-Bx7mR3Pa.js" />
+<link rel="preload" as="font" crossorigin href="/assets/inter-Bq7xK2mP.woff2" />"#;
+
+        assert_eq!(html, expected);
+    }
+
+    #[test]
+    fn tracks_entry_tags_for_turbo() {
+        let opts = ViteOptions::default()
+            .mode(ViteMode::Production)
+            .turbo_track(true)
+            .source(Some(SAMPLE_MANIFEST));
+
+        let vite = Vite::with_options(opts);
+        let html = vite.to_html(vec!["views/foo.js"]).unwrap();
+        let expected = r#"<link rel="stylesheet" href="/assets/shared-ChJ_j-JJ.css" data-turbo-track="reload" />
+<link rel="stylesheet" href="/assets/foo-5UjPuW-k.css" data-turbo-track="reload" />
+<script type="module" src="/assets/foo-BRBmoGS9.js" data-turbo-track="reload"></script>
+<link rel="modulepreload" href="/assets/shared-B7PI925R.js" />"#;
+
+        assert_eq!(html, expected);
+    }
+
+    #[test]
+    fn can_read_manifest_from_reader() {
+        let opts = ViteOptions::default()
+            .mode(ViteMode::Production)
+            .manifest_reader(Box::new(|| {
+                Ok(Box::new(std::io::Cursor::new(SAMPLE_MANIFEST.as_bytes())))
+            }));
+
+        let vite = Vite::with_options(opts);
+        let html = vite.to_html(vec!["views/foo.js"]).unwrap();
+        let expected = r#"<link rel="stylesheet" href="/assets/shared-ChJ_j-JJ.css" />
+<link rel="stylesheet" href="/assets/foo-5UjPuW-k.css" />
+<script type="module" src="/assets/foo-BRBmoGS9.js"></script>
+<link rel="modulepreload" href="/assets/shared-B7PI925R.js" />"#;
+
+        assert_eq!(html, expected);
+    }
+
+    #[test]
+    fn can_reject_html_from_reader() {
+        let opts = ViteOptions::default()
+            .mode(ViteMode::Production)
+            .manifest_reader(Box::new(|| {
+                let page = "\n  <!DOCTYPE html><html><body>502 Bad Gateway</body></html>";
+                Ok(Box::new(std::io::Cursor::new(page.as_bytes())))
+            }));
+
+        let vite = Vite::with_options(opts);
+        let result = vite.to_html(vec!["views/foo.js"]);
+
+        assert!(matches!(
+            result,
+            Err(crate::Error::ManifestNotJson(snippet)) if snippet.starts_with("<!DOCTYPE html>")
+        ));
+    }
+
+    #[test]
+    fn can_build_link_headers() {
+        let opts = ViteOptions::default()
+            .mode(ViteMode::Production)
+            .source(Some(SAMPLE_MANIFEST));
+
+        let vite = Vite::with_options(opts);
+        let headers = vite.link_headers(vec!["views/foo.js"]).unwrap();
+
+        assert_eq!(
+            headers,
+            vec![
+                "</assets/shared-ChJ_j-JJ.css>; rel=preload; as=style",
+                "</assets/foo-5UjPuW-k.css>; rel=preload; as=style",
+                "</assets/foo-BRBmoGS9.js>; rel=modulepreload",
+                "</assets/shared-B7PI925R.js>; rel=modulepreload",
+            ]
+        );
+    }
+
+    #[test]
+    fn prepends_modulepreload_polyfill_when_enabled() {
+        let opts = ViteOptions::default()
+            .mode(ViteMode::Production)
+            .modulepreload_polyfill(true)
+            .source(Some(SAMPLE_MANIFEST));
+
+        let vite = Vite::with_options(opts);
+        let html = vite.to_html(vec!["views/foo.js"]).unwrap();
+        let polyfill = format!("<script>{MODULEPRELOAD_POLYFILL}</script>\n");
+
+        assert!(html.starts_with(&polyfill));
+        assert!(html[polyfill.len()..].starts_with(r#"<link rel="stylesheet""#));
+
+        let vite = Vite::with_options(
+            ViteOptions::default()
+                .mode(ViteMode::Production)
+                .source(Some(SAMPLE_MANIFEST)),
+        );
+        let html = vite.to_html(vec!["views/foo.js"]).unwrap();
+
+        assert!(!html.contains(MODULEPRELOAD_POLYFILL));
+    }
+
+    #[test]
+    fn derives_hmr_url_in_development() {
+        let vite = Vite::with_options(ViteOptions::default().mode(ViteMode::Development));
+        assert_eq!(vite.hmr_url(), "ws://localhost:5173/");
+
+        let vite = Vite::with_options(
+            ViteOptions::default()
+                .mode(ViteMode::Development)
+                .host("https://vite.example.com"),
+        );
+        assert_eq!(vite.hmr_url(), "wss://vite.example.com/");
+    }
+
+    #[test]
+    fn hmr_url_is_empty_in_production() {
+        let vite = Vite::with_options(ViteOptions::default().mode(ViteMode::Production));
+        assert_eq!(vite.hmr_url(), "");
+    }
+
+    #[test]
+    fn renders_client_attributes() {
+        let opts = ViteOptions::default()
+            .mode(ViteMode::Development)
+            .client_attributes(vec![
+                ("data-vite-client".to_string(), String::new()),
+                ("nonce".to_string(), "r4nd0m".to_string()),
+            ]);
+
+        let vite = Vite::with_options(opts);
+        let html = vite.to_html(vec!["app.js"]).unwrap();
+        let expected = r#"<script type="module" src="http://localhost:5173/@vite/client" data-vite-client="" nonce="r4nd0m"></script>
+<script type="module" src="http://localhost:5173/app.js"></script>"#;
+
+        assert_eq!(html, expected);
+    }
+
+    #[test]
+    fn escapes_client_attributes() {
+        let opts = ViteOptions::default()
+            .mode(ViteMode::Development)
+            .client_attributes(vec![
+                (
+                    "nonce".to_string(),
+                    r#""><script>alert(1)</script>"#.to_string(),
+                ),
+                ("onload=alert(1) x".to_string(), String::new()),
+                (String::new(), "empty".to_string()),
+            ]);
+
+        let vite = Vite::with_options(opts);
+        let html = vite.to_html(vec!["app.js"]).unwrap();
+        let expected = r#"<script type="module" src="http://localhost:5173/@vite/client" nonce="&quot;&gt;&lt;script&gt;alert(1)&lt;/script&gt;"></script>
+<script type="module" src="http://localhost:5173/app.js"></script>"#;
+
+        assert_eq!(html, expected);
+    }
+
+    #[test]
+    fn caches_rendered_html_until_invalidation() {
+        let path = std::env::temp_dir().join(format!("in-vite-{}-cached.json", std::process::id()));
+        std::fs::write(&path, SAMPLE_MANIFEST).unwrap();
+
+        let opts = ViteOptions::default()
+            .mode(ViteMode::Production)
+            .cache_rendered(true)
+            .manifest_path(path.to_string_lossy());
+
+        let vite = Vite::with_options(opts);
+        let first = vite.to_html(vec!["views/foo.js", "views/bar.js"]).unwrap();
+
+        std::fs::write(
+            &path,
+            SAMPLE_MANIFEST.replace("foo-BRBmoGS9", "foo-Xk29sLq0"),
+        )
+        .unwrap();
+        vite.manifest
+            .write()
+            .unwrap()
+            .take()
+            .expect("manifest should be cached");
+
+        let cached = vite.to_html(vec!["views/foo.js", "views/bar.js"]).unwrap();
+
+        // Reversed entrypoints are rendered on their own, i.e. uncached.
+        let reversed = vite.to_html(vec!["views/bar.js", "views/foo.js"]).unwrap();
+
+        vite.invalidate_manifest();
+        let after = vite.to_html(vec!["views/foo.js", "views/bar.js"]).unwrap();
+        std::fs::remove_file(&path).unwrap();
+
+        assert_eq!(first, cached);
+        assert!(reversed.contains("/assets/foo-Xk29sLq0.js"));
+        assert!(after.contains("/assets/foo-Xk29sLq0.js"));
+    }
+
+    #[test]
+    fn discards_renders_started_before_invalidation() {
+        let opts = ViteOptions::default()
+            .mode(ViteMode::Production)
+            .cache_rendered(true)
+            .source(Some(SAMPLE_MANIFEST));
+
+        let vite = Vite::with_options(opts);
+        let generation = vite.rendered.read().unwrap().generation;
+
+        vite.invalidate_manifest();
+        vite.cache_rendered_html(generation, "stale".to_string(), "<stale>".to_string());
+
+        assert!(vite.rendered.read().unwrap().html.is_empty());
+    }
+
+    #[test]
+    fn can_inspect_options() {
+        let opts = ViteOptions::default()
+            .host("http://localhost:3000")
+            .mode(ViteMode::Production)
+            .manifest_path("public/.vite/manifest.json")
+            .source(Some(SAMPLE_MANIFEST));
+
+        assert_eq!(opts.get_host(), "http://localhost:3000");
+        assert_eq!(opts.get_mode(), &ViteMode::Production);
+        assert_eq!(opts.get_manifest_path(), "public/.vite/manifest.json");
+        assert_eq!(opts.get_manifest_source(), Some(SAMPLE_MANIFEST));
+
+        let opts = ViteOptions::default();
+        assert_eq!(opts.get_manifest_path(), "dist/.vite/manifest.json");
+        assert_eq!(opts.get_manifest_source(), None);
+    }
+
+    #[test]
+    #[traced_test]
+    fn falls_back_to_dev_without_manifest() {
+        let opts = ViteOptions::default()
+            .mode(ViteMode::Production)
+            .manifest_path("does/not/exist/manifest.json")
+            .fallback_to_dev(true);
+
+        let vite = Vite::with_options(opts);
+        let html = vite.to_html(vec!["app.js"]).unwrap();
+        let expected = r#"<script type="module" src="http://localhost:5173/@vite/client"></script>
+<script type="module" src="http://localhost:5173/app.js"></script>"#;
+
+        assert_eq!(html, expected);
+        assert!(logs_contain("falling back to development"));
+
+        let opts = ViteOptions::default()
+            .mode(ViteMode::Production)
+            .manifest_path("does/not/exist/manifest.json");
+
+        assert!(Vite::with_options(opts).to_html(vec!["app.js"]).is_err());
+    }
+
+    /// Serializes tests depending on the environment variables the mode is
+    /// guessed from.
+    static ENV_LOCK: std::sync::Mutex<()> = std::sync::Mutex::new(());
+
+    #[test]
+    fn global_returns_same_instance() {
+        // No other test accesses the global instance, hence this is the first
+        // access in the process.
+        let _lock = ENV_LOCK.lock().unwrap_or_else(PoisonError::into_inner);
+        std::env::set_var("NODE_ENV", "production");
+        let first = Vite::global();
+        std::env::remove_var("NODE_ENV");
+
+        let second = Vite::global();
+
+        assert!(std::ptr::eq(first, second));
+        assert_eq!(first.mode(), &ViteMode::Production);
+        assert_eq!(ViteOptions::default().get_mode(), &ViteMode::Development);
+    }
+
+    #[test]
+    fn can_exclude_resources() {
+        let opts = ViteOptions::default()
+            .mode(ViteMode::Production)
+            .exclude(vec!["shared-B7PI925R.js".to_string()])
+            .source(Some(SAMPLE_MANIFEST));
+
+        let vite = Vite::with_options(opts);
+        let html = vite.to_html(vec!["views/foo.js"]).unwrap();
+        let expected = r#"<link rel="stylesheet" href="/assets/shared-ChJ_j-JJ.css" />
+<link rel="stylesheet" href="/assets/foo-5UjPuW-k.css" />
+<script type="module" src="/assets/foo-BRBmoGS9.js"></script>"#;
+
+        assert_eq!(html, expected);
+    }
+
+    #[test]
+    fn can_load_stylesheets_asynchronously() {
+        let opts = ViteOptions::default()
+            .mode(ViteMode::Production)
+            .async_css(true)
+            .source(Some(SAMPLE_MANIFEST));
+
+        let vite = Vite::with_options(opts);
+        let html = vite.to_html(vec!["views/bar.js"]).unwrap();
+        let expected = r#"<link rel="preload" as="style" href="/assets/shared-ChJ_j-JJ.css" onload="this.onload=null;this.rel='stylesheet'" /><noscript><link rel="stylesheet" href="/assets/shared-ChJ_j-JJ.css" /></noscript>
+<script type="module" src="/assets/bar-gkvgaI9m.js"></script>
+<link rel="modulepreload" href="/assets/shared-B7PI925R.js" />"#;
+
+        assert_eq!(html, expected);
+    }
+
+    #[test]
+    fn can_resolve_imports_from_additional_manifests() {
+        let opts = ViteOptions::default()
+            .mode(ViteMode::Production)
+            .source(Some(include_str!("../test/app_manifest.json")))
+            .additional_manifests(vec!["test/vendor_manifest.json".to_string()]);
+
+        let vite = Vite::with_options(opts);
+        let html = vite.to_html(vec!["views/app.js"]).unwrap();
+        let expected = r#"<link rel="stylesheet" href="/assets/vendor-Dz4Hw7Pa.css" />
+<script type="module" src="/assets/app-Yb2Uc5Mr.js"></script>
+<link rel="modulepreload" href="/assets/vendor-Tq8Ns3Lk.js" />"#;
+
+        assert_eq!(html, expected);
+    }
+
+    #[test]
+    fn can_import_stylesheets() {
+        let opts = ViteOptions::default()
+            .mode(ViteMode::Production)
+            .css_mode(ViteCssMode::Import)
+            .source(Some(SAMPLE_MANIFEST));
+
+        let vite = Vite::with_options(opts);
+        let html = vite.to_html(vec!["views/foo.js"]).unwrap();
+        let expected = r#"<style>@import url("/assets/shared-ChJ_j-JJ.css");@import url("/assets/foo-5UjPuW-k.css");</style>
+<script type="module" src="/assets/foo-BRBmoGS9.js"></script>
+<link rel="modulepreload" href="/assets/shared-B7PI925R.js" />"#;
+
+        assert_eq!(html, expected);
+    }
+
+    #[test]
+    fn base_tag_points_at_base() {
+        let opts = ViteOptions::default()
+            .mode(ViteMode::Production)
+            .base("/app/");
+
+        let vite = Vite::with_options(opts);
+        assert_eq!(vite.base_tag(), r#"<base href="/app/">"#);
+    }
+
+    #[test]
+    fn base_tag_is_empty_without_base() {
+        let vite = Vite::with_options(ViteOptions::default().mode(ViteMode::Production));
+        assert_eq!(vite.base_tag(), "");
+    }
+
+    #[test]
+    fn can_diagnose_entrypoints() {
+        let vite = Vite::for_testing(SAMPLE_MANIFEST, ViteMode::Production);
+        let report = vite
+            .diagnose(vec![
+                "views/foo.js",
+                "_shared-B7PI925R.js",
+                "views/missing.js",
+            ])
+            .unwrap();
+
+        assert_eq!(
+            report.status("views/foo.js"),
+            Some(&ResolutionStatus::Resolved(4))
+        );
+        assert_eq!(
+            report.status("_shared-B7PI925R.js"),
+            Some(&ResolutionStatus::NotEntry)
+        );
+        assert_eq!(
+            report.status("views/missing.js"),
+            Some(&ResolutionStatus::NotFound)
+        );
+    }
+
+    #[test]
+    fn diagnose_reports_missing_imports() {
+        let vite = Vite::for_testing(
+            include_str!("../test/app_manifest.json"),
+            ViteMode::Production,
+        );
+        let report = vite.diagnose(vec!["views/app.js"]).unwrap();
+
+        assert_eq!(
+            report.status("views/app.js"),
+            Some(&ResolutionStatus::MissingImports(vec![
+                "_vendor-Tq8Ns3Lk.js".to_string()
+            ]))
+        );
+    }
+
+    #[test]
+    fn can_join_tags_with_custom_separator() {
+        let opts = ViteOptions::default()
+            .mode(ViteMode::Production)
+            .line_separator("\r\n")
+            .source(Some(SAMPLE_MANIFEST));
+
+        let vite = Vite::with_options(opts);
+        let html = vite.to_html(vec!["views/foo.js"]).unwrap();
+        let expected = [
+            r#"<link rel="stylesheet" href="/assets/shared-ChJ_j-JJ.css" />"#,
+            r#"<link rel="stylesheet" href="/assets/foo-5UjPuW-k.css" />"#,
+            r#"<script type="module" src="/assets/foo-BRBmoGS9.js"></script>"#,
+            r#"<link rel="modulepreload" href="/assets/shared-B7PI925R.js" />"#,
+        ];
+
+        assert_eq!(html, expected.join("\r\n"));
+    }
+
+    #[test]
+    fn can_join_tags_without_separator() {
+        let opts = ViteOptions::default()
+            .mode(ViteMode::Development)
+            .line_separator("");
+
+        let vite = Vite::with_options(opts);
+        let html = vite.to_html(vec!["app.js"]).unwrap();
+        let expected = r#"<script type="module" src="http://localhost:5173/@vite/client"></script><script type="module" src="http://localhost:5173/app.js"></script>"#;
+
+        assert_eq!(html, expected);
+    }
+
+    #[test]
+    fn resolves_path_relative_to_exe() {
+        let exe = std::path::Path::new("opt").join("app").join("server");
+        let path = exe_relative_path(Ok(exe), "dist/.vite/manifest.json");
+        let expected = std::path::Path::new("opt")
+            .join("app")
+            .join("dist/.vite/manifest.json");
+
+        assert_eq!(path, expected.to_string_lossy());
+    }
+
+    #[test]
+    #[traced_test]
+    fn falls_back_to_path_without_exe() {
+        let err = std::io::Error::new(std::io::ErrorKind::NotFound, "no executable");
+        let path = exe_relative_path(Err(err), "dist/.vite/manifest.json");
+
+        assert_eq!(path, "dist/.vite/manifest.json");
+        assert!(logs_contain("failed to locate the current executable"));
+    }
+
+    #[test]
+    fn can_set_manifest_path_relative_to_exe() {
+        let opts = ViteOptions::default().manifest_path_relative_to_exe("manifest.json");
+        let exe = std::env::current_exe().unwrap();
+        let expected = exe.parent().unwrap().join("manifest.json");
+
+        assert_eq!(opts.get_manifest_path(), expected.to_string_lossy());
+    }
+
+    #[test]
+    fn can_preload_stylesheets_of_imports() {
+        let opts = ViteOptions::default()
+            .mode(ViteMode::Production)
+            .preload_import_css(true)
+            .source(Some(SAMPLE_MANIFEST));
+
+        let vite = Vite::with_options(opts);
+        let html = vite.to_html(vec!["views/foo.js"]).unwrap();
+        let expected = r#"<link rel="stylesheet" href="/assets/foo-5UjPuW-k.css" />
+<script type="module" src="/assets/foo-BRBmoGS9.js"></script>
+<link rel="modulepreload" href="/assets/shared-B7PI925R.js" />
+<link rel="preload" as="style" href="/assets/shared-ChJ_j-JJ.css" />"#;
+
+        assert_eq!(html, expected);
+    }
+
+    #[test]
+    fn can_read_chunk_sizes() {
+        let vite = Vite::for_testing(
+            include_str!("../test/sizes_manifest.json"),
+            ViteMode::Production,
+        );
+
+        let app = vite.chunk_metadata("views/app.js").unwrap().unwrap();
+        assert_eq!(app.file, "assets/app-Qw7Ep3Zn.js");
+        assert_eq!(app.size, Some(48213));
+        assert_eq!(app.gzip_size, Some(15872));
+
+        let admin = vite.chunk_metadata("views/admin.js").unwrap().unwrap();
+        assert_eq!(admin.size, None);
+        assert_eq!(admin.gzip_size, None);
+
+        assert_eq!(vite.chunk_metadata("views/missing.js").unwrap(), None);
+    }
+
+    #[test]
+    fn can_preload_entry_instead_of_executing() {
+        let opts = ViteOptions::default()
+            .mode(ViteMode::Production)
+            .entry_as_preload(true)
+            .source(Some(SAMPLE_MANIFEST));
+
+        let vite = Vite::with_options(opts);
+        let html = vite.to_html(vec!["views/foo.js"]).unwrap();
+        let expected = r#"<link rel="stylesheet" href="/assets/shared-ChJ_j-JJ.css" />
+<link rel="stylesheet" href="/assets/foo-5UjPuW-k.css" />
+<link rel="modulepreload" href="/assets/foo-BRBmoGS9.js" />
+<link rel="modulepreload" href="/assets/shared-B7PI925R.js" />"#;
+
+        assert_eq!(html, expected);
+    }
+
+    #[test]
+    fn can_list_all_stylesheets() {
+        let opts = ViteOptions::default()
+            .mode(ViteMode::Production)
+            .base("/static/")
+            .source(Some(SAMPLE_MANIFEST));
+
+        let vite = Vite::with_options(opts);
+        let stylesheets = vite.all_stylesheets().unwrap();
+
+        assert_eq!(
+            stylesheets,
+            vec![
+                "/static/assets/foo-5UjPuW-k.css",
+                "/static/assets/shared-ChJ_j-JJ.css",
+            ]
+        );
+    }
+
+    #[test]
+    fn can_explain_resolution() {
+        let vite = Vite::for_testing(SAMPLE_MANIFEST, ViteMode::Production);
+        let plan = vite.explain(vec!["views/foo.js"]).unwrap();
+        let plan: serde_json::Value = serde_json::from_str(&plan).unwrap();
+
+        let resources = plan["resources"].as_array().unwrap();
+        let resources: Vec<(&str, &str, &str)> = resources
+            .iter()
+            .map(|resource| {
+                (
+                    resource["kind"].as_str().unwrap(),
+                    resource["url"].as_str().unwrap(),
+                    resource["chunk"].as_str().unwrap(),
+                )
+            })
+            .collect();
+
+        assert_eq!(plan["entrypoints"], serde_json::json!(["views/foo.js"]));
+        assert_eq!(
+            resources,
+            vec![
+                (
+                    "stylesheet",
+                    "/assets/shared-ChJ_j-JJ.css",
+                    "_shared-B7PI925R.js"
+                ),
+                ("stylesheet", "/assets/foo-5UjPuW-k.css", "views/foo.js"),
+                ("module", "/assets/foo-BRBmoGS9.js", "views/foo.js"),
+                (
+                    "preload_module",
+                    "/assets/shared-B7PI925R.js",
+                    "_shared-B7PI925R.js"
+                ),
+            ]
+        );
+    }
+
+    #[test]
+    fn skips_chunks_with_empty_file() {
+        let vite = Vite::for_testing(
+            include_str!("../test/empty_file_manifest.json"),
+            ViteMode::Production,
+        );
+
+        let html = vite.to_html(vec!["views/app.js"]).unwrap();
+        let expected = r#"<link rel="stylesheet" href="/assets/virtual-Kp4Rz8Wm.css" />
+<script type="module" src="/assets/app-Hv6Nc2Ld.js"></script>"#;
+
+        assert_eq!(html, expected);
+        assert!(!html.contains("href=\"\""));
+    }
+
+    #[test]
+    fn can_include_legacy_chunks() {
+        let opts = ViteOptions::default()
+            .mode(ViteMode::Production)
+            .legacy(true)
+            .source(Some(include_str!("../test/legacy_manifest.json")));
+
+        let vite = Vite::with_options(opts);
+        let html = vite.to_html(vec!["views/app.js"]).unwrap();
+        let expected = r#"<script nomodule id="vite-legacy-polyfill" src="/assets/polyfills-legacy-Dk7Vb4Qs.js"></script>
+<link rel="stylesheet" href="/assets/shared-ChJ_j-JJ.css" />
+<script type="module" src="/assets/app-Rz5kM1Nq.js"></script>
+<link rel="modulepreload" href="/assets/shared-B7PI925R.js" />
+<script nomodule>System.import("/assets/app-legacy-Tm3Hc9Lp.js")</script>"#;
+
+        assert_eq!(html, expected);
+    }
+
+    #[test]
+    fn excludes_legacy_chunks_by_default() {
+        let vite = Vite::for_testing(
+            include_str!("../test/legacy_manifest.json"),
+            ViteMode::Production,
+        );
+
+        let html = vite.to_html(vec!["views/app.js"]).unwrap();
+
+        assert!(!html.contains("nomodule"));
+        assert!(!html.contains("legacy"));
+    }
+
+    #[test]
+    fn can_access_manifest() {
+        let vite = Vite::for_testing(SAMPLE_MANIFEST, ViteMode::Production);
+        let entries = vite
+            .with_manifest(|manifest| manifest.iter().filter(|(_, chunk)| chunk.is_entry).count())
+            .unwrap();
+
+        assert_eq!(entries, 2);
+    }
+
+    #[test]
+    fn no_guess_ignores_environment() {
+        let _lock = ENV_LOCK.lock().unwrap_or_else(PoisonError::into_inner);
+        std::env::set_var("NODE_ENV", "production");
+        let guessed = ViteOptions::default();
+        let opts = ViteOptions::default().no_guess();
+        std::env::remove_var("NODE_ENV");
+
+        assert_eq!(guessed.get_mode(), &ViteMode::Production);
+        assert_eq!(opts.get_mode(), &ViteMode::Development);
+    }
+
+    #[test]
+    fn no_guess_keeps_explicit_mode() {
+        let opts = ViteOptions::default().mode(ViteMode::Production).no_guess();
+
+        assert_eq!(opts.get_mode(), &ViteMode::Production);
+    }
+
+    #[test]
+    fn loads_shared_modules_from_shared_origin() {
+        let opts = ViteOptions::default()
+            .mode(ViteMode::Production)
+            .shared_origin(Some("https://shared.example.com/"))
+            .source(Some(include_str!("../test/federation_manifest.json")));
+
+        let vite = Vite::with_options(opts);
+        let html = vite.to_html(vec!["views/app.js"]).unwrap();
+        let expected = r#"<script type="module" src="/assets/app-Pw3Xk8Qd.js"></script>
+<link rel="modulepreload" crossorigin href="https://shared.example.com/shared/react-Lm7Fv2Ts.js" />"#;
+
+        assert_eq!(html, expected);
+    }
+
+    #[test]
+    fn resolves_and_hints_shared_modules_from_shared_origin() {
+        let opts = ViteOptions::default()
+            .mode(ViteMode::Production)
+            .shared_origin(Some("https://shared.example.com/"))
+            .source(Some(include_str!("../test/federation_manifest.json")));
+
+        let vite = Vite::with_options(opts);
+        let urls: Vec<String> = vite
+            .resolve(vec!["views/app.js"])
+            .unwrap()
+            .into_iter()
+            .map(|asset| asset.url)
+            .collect();
+        assert_eq!(
+            urls,
+            vec![
+                "/assets/app-Pw3Xk8Qd.js",
+                "https://shared.example.com/shared/react-Lm7Fv2Ts.js",
+            ]
+        );
+
+        let headers = vite.link_headers(vec!["views/app.js"]).unwrap();
+        assert_eq!(
+            headers,
+            vec![
+                "</assets/app-Pw3Xk8Qd.js>; rel=modulepreload",
+                "<https://shared.example.com/shared/react-Lm7Fv2Ts.js>; rel=modulepreload; crossorigin",
+            ]
+        );
+    }
+
+    #[test]
+    fn can_diff_manifests() {
+        let vite = Vite::for_testing(SAMPLE_MANIFEST, ViteMode::Production);
+        let previous = SAMPLE_MANIFEST.replace("assets/foo-BRBmoGS9.js", "assets/foo-Dq3Lp8Xs.js");
+        let diff = vite.diff_manifest(&previous).unwrap();
+
+        assert!(diff.added.is_empty());
+        assert!(diff.removed.is_empty());
+        assert_eq!(diff.changed, vec!["/assets/foo-BRBmoGS9.js"]);
+    }
+
+    #[test]
+    fn can_resolve_entrypoints_case_insensitively() {
+        let opts = ViteOptions::default()
+            .mode(ViteMode::Production)
+            .case_insensitive_lookup(true)
+            .source(Some(SAMPLE_MANIFEST));
+
+        let vite = Vite::with_options(opts);
+        let html = vite.to_html(vec!["Views/Foo.js"]).unwrap();
+        let expected = r#"<link rel="stylesheet" href="/assets/shared-ChJ_j-JJ.css" />
+<link rel="stylesheet" href="/assets/foo-5UjPuW-k.css" />
+<script type="module" src="/assets/foo-BRBmoGS9.js"></script>
+<link rel="modulepreload" href="/assets/shared-B7PI925R.js" />"#;
+
+        assert_eq!(html, expected);
+    }
+
+    #[test]
+    fn resolves_entrypoints_case_sensitively_by_default() {
+        let vite = Vite::for_testing(SAMPLE_MANIFEST, ViteMode::Production);
+        let html = vite.to_html(vec!["Views/Foo.js"]).unwrap();
+
+        assert_eq!(html, "");
+    }
+
+    #[test]
+    fn can_construct_options_from_manifest_source() {
+        let vite = Vite::with_options(SAMPLE_MANIFEST.into());
+        let html = vite.to_html(vec!["views/bar.js"]).unwrap();
+        let expected = r#"<link rel="stylesheet" href="/assets/shared-ChJ_j-JJ.css" />
+<script type="module" src="/assets/bar-gkvgaI9m.js"></script>
+<link rel="modulepreload" href="/assets/shared-B7PI925R.js" />"#;
+
+        assert_eq!(vite.mode(), &ViteMode::Production);
+        assert_eq!(html, expected);
+    }
+
+    #[test]
+    fn can_wrap_tags_in_debug_comments() {
+        let opts = ViteOptions::default()
+            .mode(ViteMode::Production)
+            .debug_comments(true)
+            .source(Some(SAMPLE_MANIFEST));
+
+        let vite = Vite::with_options(opts);
+        let html = vite.to_html(vec!["views/bar.js"]).unwrap();
+        let expected = r#"<!-- in-vite: mode=production entries=views/bar.js -->
+<link rel="stylesheet" href="/assets/shared-ChJ_j-JJ.css" />
+<script type="module" src="/assets/bar-gkvgaI9m.js"></script>
+<link rel="modulepreload" href="/assets/shared-B7PI925R.js" />
+<!-- /in-vite -->"#;
+
+        assert_eq!(html, expected);
+    }
+
+    #[test]
+    fn omits_debug_comments_by_default() {
+        let vite = Vite::for_testing(SAMPLE_MANIFEST, ViteMode::Production);
+        let html = vite.to_html(vec!["views/bar.js"]).unwrap();
+
+        assert!(!html.contains("<!--"));
+    }
+
+    #[test]
+    fn serves_entries_lacking_from_manifest_from_dev_server() {
+        let opts = ViteOptions::default()
+            .mode(ViteMode::Production)
+            .per_entry_dev_fallback(true)
+            .source(Some(SAMPLE_MANIFEST));
+
+        let vite = Vite::with_options(opts);
+        let html = vite.to_html(vec!["views/bar.js", "views/beta.js"]).unwrap();
+        let expected = r#"<link rel="stylesheet" href="/assets/shared-ChJ_j-JJ.css" />
+<script type="module" src="/assets/bar-gkvgaI9m.js"></script>
+<link rel="modulepreload" href="/assets/shared-B7PI925R.js" />
+<script type="module" src="http://localhost:5173/@vite/client"></script>
+<script type="module" src="http://localhost:5173/views/beta.js"></script>"#;
+
+        assert_eq!(html, expected);
+    }
+
+    #[test]
+    fn serves_entries_lacking_from_manifest_from_given_host() {
+        let opts = ViteOptions::default()
+            .mode(ViteMode::Production)
+            .per_entry_dev_fallback(true)
+            .source(Some(SAMPLE_MANIFEST));
+
+        let vite = Vite::with_options(opts);
+        let html = vite
+            .to_html_with_host(
+                vec!["views/beta.js", "views/bar.js"],
+                "http://10.0.0.2:5173",
+            )
+            .unwrap();
+        let expected = r#"<link rel="stylesheet" href="http://10.0.0.2:5173/assets/shared-ChJ_j-JJ.css" />
+<script type="module" src="http://10.0.0.2:5173/assets/bar-gkvgaI9m.js"></script>
+<link rel="modulepreload" href="http://10.0.0.2:5173/assets/shared-B7PI925R.js" />
+<script type="module" src="http://10.0.0.2:5173/@vite/client"></script>
+<script type="module" src="http://10.0.0.2:5173/views/beta.js"></script>"#;
+
+        assert_eq!(html, expected);
+    }
+
+    #[test]
+    fn reports_whether_manifest_is_loaded() {
+        let vite = Vite::for_testing(SAMPLE_MANIFEST, ViteMode::Production);
+        assert!(!vite.manifest_loaded());
+
+        vite.to_html(vec!["views/foo.js"]).unwrap();
+        assert!(vite.manifest_loaded());
+
+        vite.invalidate_manifest();
+        assert!(!vite.manifest_loaded());
+    }
+
+    #[test]
+    fn reports_manifest_path() {
+        let opts = ViteOptions::default()
+            .mode(ViteMode::Production)
+            .manifest_path("test/missing_manifest.json");
+
+        let vite = Vite::with_options(opts);
+        assert_eq!(vite.manifest_path(), "test/missing_manifest.json");
+        assert!(vite.to_html(vec!["views/foo.js"]).is_err());
+        assert!(!vite.manifest_loaded());
+    }
+
+    #[test]
+    fn can_render_client_manifest_script() {
+        let vite = Vite::for_testing(SAMPLE_MANIFEST, ViteMode::Production);
+        let script = vite.client_manifest_script().unwrap();
+        let json = script
+            .strip_prefix(r#"<script type="application/json" id="vite-manifest">"#)
+            .and_then(|script| script.strip_suffix("</script>"))
+            .unwrap();
+
+        let expected = serde_json::json!({
+            "views/bar.js": [
+                "/assets/shared-ChJ_j-JJ.css",
+                "/assets/bar-gkvgaI9m.js",
+                "/assets/shared-B7PI925R.js",
+            ],
+            "views/foo.js": [
+                "/assets/shared-ChJ_j-JJ.css",
+                "/assets/foo-5UjPuW-k.css",
+                "/assets/foo-BRBmoGS9.js",
+                "/assets/shared-B7PI925R.js",
+            ],
+        });
+
+        assert_eq!(
+            serde_json::from_str::<serde_json::Value>(json).unwrap(),
+            expected
+        );
+    }
+
+    #[test]
+    fn omits_client_manifest_script_in_development() {
+        let vite = Vite::for_testing(SAMPLE_MANIFEST, ViteMode::Development);
+
+        assert_eq!(vite.client_manifest_script().unwrap(), "");
+    }
+
+    #[test]
+    fn falls_back_to_vite4_manifest_location() {
+        let out_dir = std::env::temp_dir().join(format!("in-vite-{}-vite4", std::process::id()));
+        std::fs::create_dir_all(&out_dir).unwrap();
+        std::fs::write(out_dir.join("manifest.json"), SAMPLE_MANIFEST).unwrap();
+
+        let mut opts = ViteOptions::default().mode(ViteMode::Production);
+        opts.manifest_path = out_dir.join(".vite/manifest.json").to_string_lossy().into();
+        let vite = Vite::with_options(opts);
+        let html = vite.to_html(vec!["views/foo.js"]);
+
+        let explicit = ViteOptions::default()
+            .mode(ViteMode::Production)
+            .manifest_path(out_dir.join(".vite/manifest.json").to_string_lossy());
+        let explicit = Vite::with_options(explicit).to_html(vec!["views/foo.js"]);
+        std::fs::remove_dir_all(&out_dir).unwrap();
+
+        assert!(html.unwrap().contains("/assets/foo-BRBmoGS9.js"));
+        assert!(explicit.is_err());
+    }
+
+    #[test]
+    fn renders_explicit_resources_like_manifest_resources() {
+        let vite = Vite::for_testing(SAMPLE_MANIFEST, ViteMode::Production);
+        let resources = [
+            (AssetKind::Stylesheet, "assets/shared-ChJ_j-JJ.css"),
+            (AssetKind::Module, "assets/bar-gkvgaI9m.js"),
+            (AssetKind::PreloadModule, "assets/shared-B7PI925R.js"),
+        ];
+
+        assert_eq!(
+            render_tags(&resources, &TagOptions::default()),
+            vite.to_html(vec!["views/bar.js"]).unwrap()
+        );
+    }
+
+    #[test]
+    fn renders_explicit_resources_with_configured_options() {
+        let opts = ViteOptions::default()
+            .mode(ViteMode::Production)
+            .xhtml(false)
+            .line_separator("")
+            .url_transform(Box::new(|url| format!("{url}?v=1")))
+            .source(Some(SAMPLE_MANIFEST));
+
+        let tag_opts = TagOptions::from(&opts);
+        let vite = Vite::with_options(opts);
+        let resources = [
+            (AssetKind::Stylesheet, "assets/shared-ChJ_j-JJ.css"),
+            (AssetKind::Module, "assets/bar-gkvgaI9m.js"),
+            (AssetKind::PreloadModule, "assets/shared-B7PI925R.js"),
+        ];
+
+        assert_eq!(
+            render_tags(&resources, &tag_opts),
+            vite.to_html(vec!["views/bar.js"]).unwrap()
+        );
+    }
+
+    #[test]
+    fn omits_preloads_of_executed_modules() {
+        let opts = ViteOptions::default()
+            .mode(ViteMode::Production)
+            .allow_non_entry(true)
+            .source(Some(include_str!("../test/overlap_manifest.json")));
+
+        let vite = Vite::with_options(opts);
+        let html = vite
+            .to_html(vec!["views/app.js", "_widget-Rc6Jm2Hs.js"])
+            .unwrap();
+        let expected = r#"<script type="module" src="/assets/app-Gx4Pw9Ld.js"></script>
+<script type="module" src="/assets/widget-Rc6Jm2Hs.js"></script>"#;
+
+        assert_eq!(html, expected);
+        assert_eq!(
+            vite.to_html(vec!["views/app.js"]).unwrap(),
+            r#"<script type="module" src="/assets/app-Gx4Pw9Ld.js"></script>
+<link rel="modulepreload" href="/assets/widget-Rc6Jm2Hs.js" />"#
+        );
+    }
+
+    #[test]
+    fn can_render_alternate_stylesheets() {
+        let dark = ViteStylesheetOptions::default()
+            .rel("alternate stylesheet")
+            .title("Dark");
+        let opts = ViteOptions::default()
+            .mode(ViteMode::Production)
+            .stylesheet_overrides(HashMap::from([("foo-5UjPuW-k.css".to_string(), dark)]))
+            .source(Some(SAMPLE_MANIFEST));
+
+        let vite = Vite::with_options(opts);
+        let html = vite.to_html(vec!["views/foo.js"]).unwrap();
+        let expected = r#"<link rel="stylesheet" href="/assets/shared-ChJ_j-JJ.css" />
+<link rel="alternate stylesheet" title="Dark" href="/assets/foo-5UjPuW-k.css" />
+<script type="module" src="/assets/foo-BRBmoGS9.js"></script>
+<link rel="modulepreload" href="/assets/shared-B7PI925R.js" />"#;
+
+        assert_eq!(html, expected);
+    }
+
+    #[test]
+    fn prefers_longest_stylesheet_override() {
+        let dark = ViteStylesheetOptions::default()
+            .rel("alternate stylesheet")
+            .title("Dark");
+        let light = ViteStylesheetOptions::default()
+            .rel("alternate stylesheet")
+            .title("Light");
+        let opts = ViteOptions::default()
+            .mode(ViteMode::Production)
+            .stylesheet_overrides(HashMap::from([
+                ("-k.css".to_string(), light),
+                ("foo-5UjPuW-k.css".to_string(), dark),
+            ]))
+            .source(Some(SAMPLE_MANIFEST));
+
+        let vite = Vite::with_options(opts);
+        let html = vite.to_html(vec!["views/foo.js"]).unwrap();
+
+        assert!(html.contains(
+            r#"<link rel="alternate stylesheet" title="Dark" href="/assets/foo-5UjPuW-k.css" />"#
+        ));
+    }
+
+    #[test]
+    fn can_resolve_logical_name() {
+        let vite = Vite::for_testing(SAMPLE_MANIFEST, ViteMode::Production);
+
+        assert_eq!(
+            vite.logical_name("views/foo.js").unwrap().as_deref(),
+            Some("foo")
+        );
+        assert_eq!(
+            vite.logical_name("_shared-CPdiUi_T.js").unwrap().as_deref(),
+            Some("shared")
+        );
+        assert_eq!(vite.logical_name("views/missing.js").unwrap(), None);
+    }
+
+    #[test]
+    fn guesses_production_from_configured_envs() {
+        let _lock = ENV_LOCK.lock().unwrap_or_else(PoisonError::into_inner);
+        std::env::set_var("APP_ENV", "staging");
+        let staging = ViteOptions::new()
+            .production_envs(vec!["production".to_string(), "staging".to_string()])
+            .guess_mode();
+        let default = ViteOptions::new().guess_mode();
+        std::env::remove_var("APP_ENV");
+
+        assert_eq!(staging.get_mode(), &ViteMode::Production);
+        assert_eq!(default.get_mode(), &ViteMode::Development);
+    }
+
+    #[test]
+    fn applies_referrer_policy_when_configured() {
+        let opts = ViteOptions::default()
+            .mode(ViteMode::Production)
+            .base("https://cdn.example.com/")
+            .crossorigin(ViteCrossOrigin::Anonymous)
+            .referrer_policy(Some("no-referrer"))
+            .source(Some(SAMPLE_MANIFEST));
+
+        let vite = Vite::with_options(opts);
+        let html = vite.to_html(vec!["views/bar.js"]).unwrap();
+        let expected = r#"<link rel="stylesheet" crossorigin referrerpolicy="no-referrer" href="https://cdn.example.com/assets/shared-ChJ_j-JJ.css" />
+<script type="module" crossorigin referrerpolicy="no-referrer" src="https://cdn.example.com/assets/bar-gkvgaI9m.js"></script>
+<link rel="modulepreload" crossorigin referrerpolicy="no-referrer" href="https://cdn.example.com/assets/shared-B7PI925R.js" />"#;
+
+        assert_eq!(html, expected);
+        assert!(!Vite::for_testing(SAMPLE_MANIFEST, ViteMode::Production)
+            .to_html(vec!["views/bar.js"])
+            .unwrap()
+            .contains("referrerpolicy"));
+    }
+
+    #[test]
+    fn can_render_compact_html() {
+        let vite = Vite::for_testing(SAMPLE_MANIFEST, ViteMode::Production);
+        let html = vite.to_html_compact(vec!["views/foo.js"]).unwrap();
+        let expected = concat!(
+            r#"<link rel="stylesheet" href="/assets/shared-ChJ_j-JJ.css" />"#,
+            r#"<link rel="stylesheet" href="/assets/foo-5UjPuW-k.css" />"#,
+            r#"<script type="module" src="/assets/foo-BRBmoGS9.js"></script>"#,
+            r#"<link rel="modulepreload" href="/assets/shared-B7PI925R.js" />"#,
+        );
+
+        assert_eq!(html, expected);
+        assert!(vite.to_html(vec!["views/foo.js"]).unwrap().contains('\n'));
+    }
+
+    #[test]
+    fn transforms_urls_before_rendering() {
+        let opts = ViteOptions::default()
+            .mode(ViteMode::Production)
+            .url_transform(Box::new(|url| url.to_uppercase()))
+            .source(Some(SAMPLE_MANIFEST));
+
+        let vite = Vite::with_options(opts);
+        let html = vite.to_html(vec!["views/foo.js"]).unwrap();
+        let expected = r#"<link rel="stylesheet" href="/ASSETS/SHARED-CHJ_J-JJ.CSS" />
+<link rel="stylesheet" href="/ASSETS/FOO-5UJPUW-K.CSS" />
+<script type="module" src="/ASSETS/FOO-BRBMOGS9.JS"></script>
+<link rel="modulepreload" href="/ASSETS/SHARED-B7PI925R.JS" />"#;
+
+        assert_eq!(html, expected);
+
+        let urls: Vec<String> = vite
+            .resolve(vec!["views/foo.js"])
+            .unwrap()
+            .into_iter()
+            .map(|asset| asset.url)
+            .collect();
+        assert_eq!(
+            urls,
+            vec![
+                "/ASSETS/SHARED-CHJ_J-JJ.CSS",
+                "/ASSETS/FOO-5UJPUW-K.CSS",
+                "/ASSETS/FOO-BRBMOGS9.JS",
+                "/ASSETS/SHARED-B7PI925R.JS",
+            ]
+        );
+
+        let headers = vite.link_headers(vec!["views/foo.js"]).unwrap();
+        assert_eq!(
+            headers[0],
+            "</ASSETS/SHARED-CHJ_J-JJ.CSS>; rel=preload; as=style"
+        );
+        assert!(headers.iter().all(|header| header.starts_with("</ASSETS/")));
+    }
+
+    #[test]
+    fn can_select_environment_from_grouped_manifest() {
+        let opts = ViteOptions::default()
+            .mode(ViteMode::Production)
+            .environment("edge")
+            .source(Some(include_str!("../test/environments_manifest.json")));
+
+        let vite = Vite::with_options(opts);
+        let html = vite.to_html(vec!["views/app.js"]).unwrap();
+
+        assert_eq!(
+            html,
+            r#"<script type="module" src="/edge/app-Zp8Lb4Ns.js"></script>"#
+        );
+    }
+
+    #[test]
+    fn can_render_tags_split_into_head_and_body() {
+        let opts = ViteOptions::default()
+            .mode(ViteMode::Production)
+            .modulepreload_polyfill(true)
+            .source(Some(SAMPLE_MANIFEST));
+
+        let vite = Vite::with_options(opts);
+        let tags = vite.render(vec!["views/foo.js"]).unwrap();
+        let head = format!(
+            r#"<script>{MODULEPRELOAD_POLYFILL}</script>
+<link rel="stylesheet" href="/assets/shared-ChJ_j-JJ.css" />
+<link rel="stylesheet" href="/assets/foo-5UjPuW-k.css" />
+<link rel="modulepreload" href="/assets/shared-B7PI925R.js" />"#
+        );
+
+        assert_eq!(
+            tags.to_string(),
+            vite.to_html(vec!["views/foo.js"]).unwrap()
+        );
+        assert_eq!(tags.head(), head);
+        assert_eq!(
+            tags.body(),
+            r#"<script type="module" src="/assets/foo-BRBmoGS9.js"></script>"#
+        );
+    }
+
+    #[test]
+    fn can_list_orphan_assets() {
+        let vite = Vite::for_testing(
+            include_str!("../test/orphans_manifest.json"),
+            ViteMode::Production,
+        );
+
+        assert_eq!(
+            vite.orphan_assets().unwrap(),
+            vec!["/assets/hero-Wm3Kd9Pz.webp", "/assets/inter-Bq7xK2mP.woff2"]
+        );
+    }
+
+    #[test]
+    fn can_parse_mode() {
+        assert_eq!(
+            "development".parse::<ViteMode>().unwrap(),
+            ViteMode::Development
+        );
+        assert_eq!(
+            "production".parse::<ViteMode>().unwrap(),
+            ViteMode::Production
+        );
+        assert!(matches!(
+            "staging".parse::<ViteMode>(),
+            Err(crate::Error::InvalidMode(_))
+        ));
+    }
+
+    #[test]
+    fn can_override_mode_per_render() {
+        let vite = Vite::for_testing(SAMPLE_MANIFEST, ViteMode::Production);
+        let html = vite
+            .to_html_with_mode(vec!["views/foo.js"], ViteMode::Development)
+            .unwrap();
+        let expected = r#"<script type="module" src="http://localhost:5173/@vite/client"></script>
+<script type="module" src="http://localhost:5173/views/foo.js"></script>"#;
+
+        assert_eq!(html, expected);
+        assert!(vite
+            .to_html(vec!["views/foo.js"])
+            .unwrap()
+            .contains("/assets/"));
+    }
+
+    #[test]
+    fn can_detect_manifest_version() {
+        let vite = Vite::for_testing(SAMPLE_MANIFEST, ViteMode::Production);
+        assert_eq!(vite.detect_manifest_version().unwrap(), 5);
+
+        let legacy = r#"{
+            "main.js": { "file": "assets/main-4f9c1b2e.js", "src": "main.js", "isEntry": true }
+        }"#;
+        let vite = Vite::for_testing(legacy, ViteMode::Production);
+        assert_eq!(vite.detect_manifest_version().unwrap(), 4);
+    }
+
+    #[test]
+    fn can_prefetch_secondary_entry() {
+        let vite = Vite::for_testing(SAMPLE_MANIFEST, ViteMode::Production);
+        let html = vite.prefetch(vec!["views/bar.js"]).unwrap();
+        let expected = r#"<link rel="prefetch" href="/assets/shared-ChJ_j-JJ.css" />
+<link rel="prefetch" href="/assets/bar-gkvgaI9m.js" />
+<link rel="prefetch" href="/assets/shared-B7PI925R.js" />"#;
+
+        assert_eq!(html, expected);
+    }
+
+    #[test]
+    fn prefetch_is_empty_in_development() {
+        let vite = Vite::for_testing(SAMPLE_MANIFEST, ViteMode::Development);
+
+        assert_eq!(vite.prefetch(vec!["views/bar.js"]).unwrap(), "");
+    }
+
+    #[test]
+    fn entry_has_no_css_in_development() {
+        let opts = ViteOptions::default()
+            .mode(ViteMode::Development)
+            .manifest_path("test/missing_manifest.json");
+
+        let vite = Vite::with_options(opts);
+
+        assert!(!vite.entry_has_css("views/foo.js").unwrap());
+    }
+
+    #[test]
+    fn preloads_fonts_shared_by_chunks_once() {
+        let opts = ViteOptions::default()
+            .mode(ViteMode::Production)
+            .source(Some(include_str!("../test/shared_fonts_manifest.json")));
+
+        let vite = Vite::with_options(opts);
+        let html = vite.font_preloads("views/app.js").unwrap();
+        let expected = r#"<link rel="preload" as="font" crossorigin href="/assets/inter-Bq7xK2mP.woff2" />
+<link rel="preload" as="font" crossorigin href="/assets/roboto-Pw4Jd6Ks.woff2" />"#;
+
+        assert_eq!(html, expected);
+    }
+
+    #[test]
+    fn loads_alternate_stylesheets_asynchronously() {
+        let contrast = ViteStylesheetOptions::default()
+            .rel("alternate stylesheet")
+            .title("High \"contrast\" & <bold>");
+        let opts = ViteOptions::default()
+            .mode(ViteMode::Production)
+            .async_css(true)
+            .stylesheet_overrides(HashMap::from([("foo-5UjPuW-k.css".to_string(), contrast)]))
+            .source(Some(SAMPLE_MANIFEST));
+
+        let vite = Vite::with_options(opts);
+        let html = vite.to_html(vec!["views/foo.js"]).unwrap();
+        let title = r#"title="High &quot;contrast&quot; &amp; &lt;bold&gt;""#;
+        let expected = format!(
+            r#"<link rel="preload" as="style" {title} href="/assets/foo-5UjPuW-k.css" onload="this.onload=null;this.rel='alternate stylesheet'" /><noscript><link rel="alternate stylesheet" {title} href="/assets/foo-5UjPuW-k.css" /></noscript>"#
+        );
+
+        assert!(html.contains(&expected));
+    }
+
+    #[test]
+    fn prefetches_with_configured_attributes() {
+        let opts = ViteOptions::default()
+            .mode(ViteMode::Production)
+            .xhtml(false)
+            .crossorigin(ViteCrossOrigin::Anonymous)
+            .referrer_policy(Some("no-referrer"))
+            .shared_origin(Some("https://shared.example.com"))
+            .source(Some(include_str!("../test/federation_manifest.json")));
+
+        let vite = Vite::with_options(opts);
+        let html = vite.prefetch(vec!["views/app.js"]).unwrap();
+
+        assert!(!html.contains("/>"));
+        assert!(html
+            .lines()
+            .all(|tag| tag.contains(r#" crossorigin referrerpolicy="no-referrer""#)));
+        assert!(html.contains(r#"href="https://shared.example.com/shared/"#));
+    }
+
+    #[test]
+    fn dev_banner_probes_client_under_base() {
+        let opts = ViteOptions::default()
+            .mode(ViteMode::Development)
+            .base("/app/");
+
+        let vite = Vite::with_options(opts);
+
+        assert!(vite
+            .dev_banner_html()
+            .contains(r#"import("http://localhost:5173/app/@vite/client")"#));
+    }
+
+    #[test]
+    fn hmr_url_includes_base() {
+        let opts = ViteOptions::default()
+            .mode(ViteMode::Development)
+            .base("/app/");
+
+        let vite = Vite::with_options(opts);
+
+        assert_eq!(vite.hmr_url(), "ws://localhost:5173/app/");
+    }
 }